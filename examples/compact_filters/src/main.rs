@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::env;
+use std::fs;
+
+use vndb_api::client::VndbApiClient;
+use vndb_api::format::error::VndbApiError;
+use vndb_api::request::filter::compact::{decode, encode};
+use vndb_api::request::filter::{Entity, Filter};
+use vndb_api::request::query::{
+    CharacterQuery, ProducerQuery, Query, QueryBuilder, ReleaseQuery, Sortable, StaffQuery, TagQuery,
+    TraitQuery, VnQuery,
+};
+
+// read by the tests of the compact module in the vndb-api crate
+const FIXTURES: &str = "../../src/request/filter/compact_fixtures.json";
+
+// filters sent to VNDB when capturing, one per kind of value the compact format encodes
+const CASES: &[(&str, &str)] = &[
+    ("vn", r#"["olang", "=", "ja"]"#),
+    ("vn", r#"["lang", "!=", "zh-Hans"]"#),
+    ("vn", r#"["id", ">=", "v17"]"#),
+    ("vn", r#"["search", "=", "Fate/stay night"]"#),
+    ("vn", r#"["released", ">=", "2020-01-01"]"#),
+    ("vn", r#"["released", "<", "2010"]"#),
+    ("vn", r#"["rating", ">", 80]"#),
+    ("vn", r#"["has_anime", "=", 1]"#),
+    ("vn", r#"["tag", "=", "g7"]"#),
+    ("vn", r#"["tag", "=", ["g7", 1, 1.5]]"#),
+    ("vn", r#"["and", ["or", ["lang", "=", "en"], ["lang", "=", "de"], ["lang", "=", "fr"]], ["olang", "!=", "ja"], ["release", "=", ["and", ["released", ">=", "2020-01-01"], ["producer", "=", ["id", "=", "p30"]]]]]"#),
+    ("vn", r#"["developer", "=", ["id", "=", "p98"]]"#),
+    ("vn", r#"["character", "=", ["seiyuu", "=", ["id", "=", "s13"]]]"#),
+    ("release", r#"["platform", "=", "swi"]"#),
+    ("release", r#"["resolution", ">=", [1920, 1080]]"#),
+    ("release", r#"["minage", "<", 18]"#),
+    ("release", r#"["vn", "=", ["id", "=", "v17"]]"#),
+    ("producer", r#"["type", "=", "co"]"#),
+    ("character", r#"["trait", "=", ["i5", 0]]"#),
+    ("character", r#"["birthday", "=", [2, 14]]"#),
+    ("character", r#"["height", ">", 170]"#),
+    ("staff", r#"["gender", "=", "f"]"#),
+    ("staff", r#"["ismain", "=", 1]"#),
+    ("tag", r#"["category", "=", "cont"]"#),
+    ("trait", r#"["search", "=", "blond"]"#),
+];
+
+#[derive(Serialize, Deserialize)]
+struct Fixture {
+    entity: String,
+    compact_filters: String,
+    normalized_filters: Value,
+}
+
+fn entity(name: &str) -> Entity {
+    match name {
+        "vn" => Entity::Vn,
+        "release" => Entity::Release,
+        "producer" => Entity::Producer,
+        "character" => Entity::Character,
+        "staff" => Entity::Staff,
+        "tag" => Entity::Tag,
+        _ => Entity::Trait,
+    }
+}
+
+fn query<T: Sortable>(filters: &str) -> Query<T> {
+    QueryBuilder::<T>::new()
+        .try_filters(filters)
+        .expect("invalid filters")
+        .results(0)
+        .enable_compact_filters()
+        .enable_normalized_filters()
+        .build()
+        .expect("invalid query")
+}
+
+// sends the filters to VNDB and keeps the compact and normalized forms it returns
+async fn capture(client: &VndbApiClient, name: &str, filters: &str) -> Result<Fixture, VndbApiError> {
+    let (compact, normalized) = match name {
+        "vn" => {
+            let r = client.vn_search(&query::<VnQuery>(filters)).await?;
+            (r.compact_filters, r.normalized_filters)
+        }
+        "release" => {
+            let r = client.release_search(&query::<ReleaseQuery>(filters)).await?;
+            (r.compact_filters, r.normalized_filters)
+        }
+        "producer" => {
+            let r = client.producer_search(&query::<ProducerQuery>(filters)).await?;
+            (r.compact_filters, r.normalized_filters)
+        }
+        "character" => {
+            let r = client.character_search(&query::<CharacterQuery>(filters)).await?;
+            (r.compact_filters, r.normalized_filters)
+        }
+        "staff" => {
+            let r = client.staff_search(&query::<StaffQuery>(filters)).await?;
+            (r.compact_filters, r.normalized_filters)
+        }
+        "tag" => {
+            let r = client.tag_search(&query::<TagQuery>(filters)).await?;
+            (r.compact_filters, r.normalized_filters)
+        }
+        _ => {
+            let r = client.trait_search(&query::<TraitQuery>(filters)).await?;
+            (r.compact_filters, r.normalized_filters)
+        }
+    };
+    Ok(Fixture {
+        entity: name.to_string(),
+        compact_filters: compact.unwrap_or_default(),
+        normalized_filters: normalized.unwrap_or_default(),
+    })
+}
+
+// checks that the local codec turns each compact string into its normalized filters and back
+fn verify(fixtures: &[Fixture]) -> usize {
+    let mut failures = 0;
+    for fixture in fixtures {
+        let entity = entity(&fixture.entity);
+        let expected = Filter::from_value(&fixture.normalized_filters).expect("invalid fixture");
+        match decode(entity, &fixture.compact_filters) {
+            Ok(decoded) if decoded == expected => {}
+            Ok(decoded) => {
+                failures += 1;
+                println!("decode {}: got {}", fixture.compact_filters, decoded.to_value());
+            }
+            Err(error) => {
+                failures += 1;
+                println!("decode {}: {}", fixture.compact_filters, error);
+            }
+        }
+        match encode(entity, &expected) {
+            Ok(encoded) if encoded == fixture.compact_filters => {}
+            Ok(encoded) => {
+                failures += 1;
+                println!("encode {}: got {}, expected {}", expected.to_value(), encoded, fixture.compact_filters);
+            }
+            Err(error) => {
+                failures += 1;
+                println!("encode {}: {}", expected.to_value(), error);
+            }
+        }
+    }
+    failures
+}
+
+#[tokio::main]
+async fn main() {
+    // `cargo run -- capture` asks VNDB for the compact form of every case and saves it
+    // `cargo run` checks the local encoder and decoder against the saved fixtures
+    if env::args().nth(1).as_deref() == Some("capture") {
+        let client = VndbApiClient::builder()
+            .build()
+            .expect("failed to build the VNDB client");
+        let mut fixtures = Vec::new();
+        for (name, filters) in CASES {
+            match capture(&client, name, filters).await {
+                Ok(fixture) => fixtures.push(fixture),
+                Err(error) => eprintln!("{} {}: {}", name, filters, error),
+            }
+        }
+        let json = serde_json::to_string_pretty(&fixtures).expect("fixtures serialize to JSON");
+        fs::write(FIXTURES, json).expect("failed to write the fixtures");
+        println!("captured {} fixtures", fixtures.len());
+    }
+
+    let fixtures: Vec<Fixture> = match fs::read_to_string(FIXTURES) {
+        Ok(json) => serde_json::from_str(&json).expect("invalid fixtures file"),
+        Err(_) => {
+            eprintln!("no {} found, run `cargo run -- capture` first", FIXTURES);
+            return;
+        }
+    };
+    let failures = verify(&fixtures);
+    println!("{} fixtures, {} mismatches", fixtures.len(), failures);
+}
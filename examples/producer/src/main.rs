@@ -2,13 +2,17 @@ use dotenvy::dotenv;
 use std::env;
 
 use vndb_api::client::VndbApiClient;
+use vndb_api::request::filter::ProducerFilter;
 use vndb_api::request::query::{QueryBuilder, ProducerQuery, ProducerField, ProducerFieldChoices, SortField};
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
     let api_key = env::var("API_KEY").expect("API_KEY must be set");
-    let api_client = VndbApiClient::new(&String::from(api_key));
+    let api_client = VndbApiClient::builder()
+        .token(&api_key)
+        .build()
+        .expect("failed to build the VNDB client");
 
     // Query producer by name, id, etc.
     // For information on the struct fields see src/format/producer.rs
@@ -17,11 +21,12 @@ async fn main() {
     // Producer queries are similar to visual novel queries 
     // see examples/vn/main.rs for detailed documentation
     let query = QueryBuilder::<ProducerQuery>::new()
-        .filters(&r#"["id", "=", "p1"]"#.to_string())
+        .filters(ProducerFilter::id().eq(1))
         .fields(ProducerFieldChoices::all())
         .enable_compact_filters()
         .enable_normalized_filters()
-        .build();
+        .build()
+        .expect("invalid query");
     if let Ok(response) = api_client.producer_search(&query).await {
         println!("{:#?}", response);
     }
@@ -29,11 +34,12 @@ async fn main() {
     // searching for the same producer that appeared in the query above
     // but by name this time
     let query = QueryBuilder::<ProducerQuery>::new()
-        .filters(&r#"["search", "=", "Yamikumo-Communications"]"#.to_string())
+        .filters(ProducerFilter::search().eq("Yamikumo-Communications"))
         .fields(ProducerFieldChoices::all())
         .enable_compact_filters()
         .enable_normalized_filters()
-        .build();
+        .build()
+        .expect("invalid query");
     if let Ok(response) = api_client.producer_search(&query).await {
         println!("{:#?}", response);
     }
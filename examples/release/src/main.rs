@@ -2,13 +2,17 @@ use dotenvy::dotenv;
 use std::env;
 
 use vndb_api::client::VndbApiClient;
+use vndb_api::request::filter::ReleaseFilter;
 use vndb_api::request::query::{QueryBuilder, ReleaseQuery, ReleaseField, ReleaseFieldChoices, SortField};
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
     let api_key = env::var("API_KEY").expect("API_KEY must be set");
-    let api_client = VndbApiClient::new(&String::from(api_key));
+    let api_client = VndbApiClient::builder()
+        .token(&api_key)
+        .build()
+        .expect("failed to build the VNDB client");
 
     // Query release by name, id, etc.
     // For information on the struct fields see src/format/release.rs
@@ -17,11 +21,12 @@ async fn main() {
     // Release queries are similar to visual novel queries 
     // see examples/vn/main.rs for detailed documentation
     let query = QueryBuilder::<ReleaseQuery>::new()
-        .filters(&r#"["id", "=", "r1"]"#.to_string())
+        .filters(ReleaseFilter::id().eq(1))
         .fields(ReleaseFieldChoices::all())
         .enable_compact_filters()
         .enable_normalized_filters()
-        .build();
+        .build()
+        .expect("invalid query");
     if let Ok(response) = api_client.release_search(&query).await {
         println!("{:#?}", response);
     }
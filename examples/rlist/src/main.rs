@@ -9,7 +9,10 @@ use vndb_api::format::ulist::UListStatus;
 async fn main() {
     dotenv().ok();
     let api_key = env::var("API_KEY").expect("API_KEY must be set");
-    let api_client = VndbApiClient::new(&String::from(api_key));
+    let api_client = VndbApiClient::builder()
+        .token(&api_key)
+        .build()
+        .expect("failed to build the VNDB client");
 
     // Add to or remove from a release list by id
     // If a release id is unknown, you can use a release query
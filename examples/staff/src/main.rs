@@ -2,13 +2,17 @@ use dotenvy::dotenv;
 use std::env;
 
 use vndb_api::client::VndbApiClient;
+use vndb_api::request::filter::StaffFilter;
 use vndb_api::request::query::{QueryBuilder, StaffQuery, StaffField, StaffFieldChoices, SortField};
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
     let api_key = env::var("API_KEY").expect("API_KEY must be set");
-    let api_client = VndbApiClient::new(&String::from(api_key));
+    let api_client = VndbApiClient::builder()
+        .token(&api_key)
+        .build()
+        .expect("failed to build the VNDB client");
 
     // Query staff by name, id, etc.
     // For information on the struct fields see src/format/staff.rs
@@ -17,11 +21,12 @@ async fn main() {
     // Staff queries are similar to visual novel queries 
     // see examples/vn/main.rs for detailed documentation
     let query = QueryBuilder::<StaffQuery>::new()
-        .filters(&r#"["id", "=", "s1"]"#.to_string())
+        .filters(StaffFilter::id().eq(1))
         .fields(StaffFieldChoices::all())
         .enable_compact_filters()
         .enable_normalized_filters()
-        .build();
+        .build()
+        .expect("invalid query");
     if let Ok(response) = api_client.staff_search(&query).await {
         println!("{:#?}", response);
     }
@@ -29,11 +34,12 @@ async fn main() {
 
     // searching for a vn staff member by name
     let query = QueryBuilder::<StaffQuery>::new()
-        .filters(&r#"["search", "=", "Yukari Tamura"]"#.to_string())
+        .filters(StaffFilter::search().eq("Yukari Tamura"))
         .fields(StaffFieldChoices::all())
         .enable_compact_filters()
         .enable_normalized_filters()
-        .build();
+        .build()
+        .expect("invalid query");
     if let Ok(response) = api_client.staff_search(&query).await {
         println!("{:#?}", response);
     }
@@ -7,7 +7,10 @@ use vndb_api::client::VndbApiClient;
 async fn main() {
     dotenv().ok();
     let api_key = env::var("API_KEY").expect("API_KEY must be set");
-    let api_client = VndbApiClient::new(&String::from(api_key));
+    let api_client = VndbApiClient::builder()
+        .token(&api_key)
+        .build()
+        .expect("failed to build the VNDB client");
 
     // Returns a few overall database statistics
     // For information on the struct fields see src/format/stats.rs
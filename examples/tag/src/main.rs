@@ -2,13 +2,17 @@ use dotenvy::dotenv;
 use std::env;
 
 use vndb_api::client::VndbApiClient;
+use vndb_api::request::filter::TagFilter;
 use vndb_api::request::query::{QueryBuilder, TagQuery, TagField, TagFieldChoices, SortField};
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
     let api_key = env::var("API_KEY").expect("API_KEY must be set");
-    let api_client = VndbApiClient::new(&String::from(api_key));
+    let api_client = VndbApiClient::builder()
+        .token(&api_key)
+        .build()
+        .expect("failed to build the VNDB client");
 
     // Query tag by name, id, etc.
     // For information on the struct fields see src/format/tag.rs
@@ -17,11 +21,12 @@ async fn main() {
     // Tag queries are similar to visual novel queries 
     // see examples/vn/main.rs for detailed documentation
     let query = QueryBuilder::<TagQuery>::new()
-        .filters(&r#"["id", "=", "g2"]"#.to_string())
+        .filters(TagFilter::id().eq(2))
         .fields(TagFieldChoices::all())
         .enable_compact_filters()
         .enable_normalized_filters()
-        .build();
+        .build()
+        .expect("invalid query");
     if let Ok(response) = api_client.tag_search(&query).await {
         println!("{:#?}", response);
     }
@@ -29,12 +34,13 @@ async fn main() {
     // this does the same as the query above except it searches by tag 
     // name instead of id, the "fantasy" = trait id "g2" in the above query
     let query = QueryBuilder::<TagQuery>::new()
-        .filters(&r#"["search", "=", "fantasy"]"#.to_string())
+        .filters(TagFilter::search().eq("fantasy"))
         .fields(TagFieldChoices::all())
         .results(3)
         .enable_compact_filters()
         .enable_normalized_filters()
-        .build();
+        .build()
+        .expect("invalid query");
     if let Ok(response) = api_client.tag_search(&query).await {
         println!("{:#?}", response);
     }
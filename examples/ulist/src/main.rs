@@ -9,7 +9,10 @@ use vndb_api::format::ulist::{UListLabelsFieldChoices, UListPatchBuilder, Date,
 async fn main() {
     dotenv().ok();
     let api_key = env::var("API_KEY").expect("API_KEY must be set");
-    let api_client = VndbApiClient::new(&String::from(api_key));
+    let api_client = VndbApiClient::builder()
+        .token(&api_key)
+        .build()
+        .expect("failed to build the VNDB client");
 
     // Fetch, add to, or remove from a user list by id
     // If you don't know a user's id, you can use a user search
@@ -31,7 +34,8 @@ async fn main() {
         .user(&String::from("u2"))
         .fields(UListFieldChoices::all())
         .results(1)
-        .build();
+        .build()
+        .expect("invalid query");
     match api_client.ulist(&query).await {
         Ok(response) => println!("{:#?}", response),
         Err(error) => eprintln!("{:#?}", error),
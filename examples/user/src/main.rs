@@ -8,7 +8,10 @@ use vndb_api::format::user::{UserDataField, UserSearchFields};
 async fn main() {
     dotenv().ok();
     let api_key = env::var("API_KEY").expect("API_KEY must be set");
-    let api_client = VndbApiClient::new(&String::from(api_key));
+    let api_client = VndbApiClient::builder()
+        .token(&api_key)
+        .build()
+        .expect("failed to build the VNDB client");
 
     // Lookup users by id or username
     // For information on the struct fields see src/format/user.rs
@@ -2,13 +2,20 @@ use dotenvy::dotenv;
 use std::env;
 
 use vndb_api::client::VndbApiClient;
-use vndb_api::request::query::{QueryBuilder, VnQuery, VnField, VnFieldChoices, SortField};
+use vndb_api::format::error::VndbApiError;
+use vndb_api::format::schema::Language;
+use vndb_api::request::filter::typed::ReleaseDate;
+use vndb_api::request::filter::{Filter, TypedFilter, VnFilter};
+use vndb_api::request::query::{QueryBuilder, VnQuery, VnField, VnFieldChoices, VnSort};
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
     let api_key = env::var("API_KEY").expect("API_KEY must be set");
-    let api_client = VndbApiClient::new(&String::from(api_key));
+    let api_client = VndbApiClient::builder()
+        .token(&api_key)
+        .build()
+        .expect("failed to build the VNDB client");
 
     // Query visual novel entries by name, id, etc.
     // For information on the struct fields see src/format/vn.rs
@@ -22,16 +29,17 @@ async fn main() {
         // this field can be blank in the form of an empty vector
         // currently this code filters by name to find a specific visual novel
         // more ways to filter can be found in the official documentation
-        .filters(&r#"["search", "=", "Saya no Uta"]"#.to_string())
+        .filters(VnFilter::search().eq("Saya no Uta"))
         // allows for selection of desired data fields can be done by hand using
         // VnFieldChoices::from() by passing in a vector of the VnField type
         // which can be found in src/request/query.rs
         .fields(VnFieldChoices::all())
-        // field to sort on, defaults to id if not used or an invalid sort field is entered
+        // field to sort on, defaults to id if not used; each query type has its own sort
+        // enum so only orders the endpoint supports can be picked
         // as is, this lists visual novels from lowest to highest rating; however this can be
         // flipped with the .reverse() function
         // more use cases for the sort field can be found in the official documentation
-        .sort(SortField::Rating)
+        .sort(VnSort::Rating)
         // limits results recieved to a number between 0 - 100 and automatically
         // adjust numbers outside of that range to fit within it
         .results(3)
@@ -53,8 +61,10 @@ async fn main() {
         // futher explained in the official documentation here:
         //      https://api.vndb.org/kana#api-structure
         .enable_normalized_filters()
-        // builds the query for compatibility with this crates client
-        .build();
+        // builds the query for compatibility with this crates client, failing if a
+        // value such as results or page is out of range
+        .build()
+        .expect("invalid query");
     // run the query
     if let Ok(response) = api_client.vn_search(&query).await {
         println!("{:#?}", response);
@@ -62,11 +72,12 @@ async fn main() {
     
     // search for visual novel by name with autocomplete options
     let query = QueryBuilder::<VnQuery>::new()
-        .filters(&r#"["search", "=", "DDLC"]"#.to_string())
+        .filters(VnFilter::search().eq("DDLC"))
         // how to limit field choices instead of using ___FieldChoices::all()
         .fields(VnFieldChoices::from(vec![VnField::Title]))
         .results(3)
-        .build();
+        .build()
+        .expect("invalid query");
     match api_client.vn_search(&query).await {
         Ok(response) => {
             response.results.iter()
@@ -82,11 +93,12 @@ async fn main() {
     // prints the name and rating for the top 3 visual novels on the site
     let query = QueryBuilder::<VnQuery>::new()
         .fields(VnFieldChoices::all())
-        .sort(SortField::Rating)
+        .sort(VnSort::Rating)
         .results(3)
         .page(1)
         .reverse()
-        .build();
+        .build()
+        .expect("invalid query");
     match api_client.vn_search(&query).await {
         Ok(response) => {
             response.results.iter()
@@ -98,13 +110,16 @@ async fn main() {
     }
 
     // error handling: example response when there is an error in the query
-    // here the filter "search" is misspelled as "serch"
-    // a helpful message is provided as well as a status code (status code documentation):
+    // here the filter "search" is misspelled as "serch", which VnFilter would not
+    // compile, so the filter is built without checking its fields
+    // the error says what kind of failure happened and carries the server's message
+    // (status code documentation):
     //      https://api.vndb.org/kana#delete-rlistid
     let query = QueryBuilder::<VnQuery>::new()
-        .filters(&r#"["serch", "=", "v101"]"#.to_string())
+        .filters(TypedFilter::unchecked(Filter::eq("serch", "v101")))
         .fields(VnFieldChoices::from(vec![VnField::Title]))
-        .build();
+        .build()
+        .expect("invalid query");
     match api_client.vn_search(&query).await {
         Ok(response) => {
             response.results.iter()
@@ -112,6 +127,12 @@ async fn main() {
                     println!("{}", vn.title.as_ref().unwrap());
                 });
         }
+        Err(VndbApiError::BadRequest { message }) => {
+            eprintln!("invalid query: {}", message);
+        }
+        Err(error) if error.is_retryable() => {
+            eprintln!("try again later: {}", error);
+        }
         Err(error) => {
             eprintln!("{:#?}", error);
         }
@@ -119,24 +140,27 @@ async fn main() {
 
     // using complex filters, another examples is listed in the vndb documentation
     //      https://api.vndb.org/kana#filters
-    let filters = r#"
-        [ "and"
-            , [ "or"
-                , [ "olang", "!=", "en" ]
-                , [ "olang", "!=", "ja" ]
-            ]
-            , [ "released", ">=", "2020-01-01" ]
-        ]
-    "#.to_string();
+    let filters = TypedFilter::and([
+        VnFilter::olang().ne(Language::English) | VnFilter::olang().ne(Language::Japanese),
+        VnFilter::released().ge(ReleaseDate::ymd(2020, 1, 1)),
+    ]);
     let query = QueryBuilder::<VnQuery>::new()
-        .filters(&filters)
+        .filters(filters)
         .results(10)
         .page(1)
-        .build();
+        .build()
+        .expect("invalid query");
     match api_client.vn_search(&query).await {
         Ok(response) => {
             println!("{:#?}", response);
         }
         Err(error) => { eprintln!("{:#?}", error); }
     }
-}
\ No newline at end of file
+
+    // filters can also be written as JSON, try_filters returns an error for
+    // invalid JSON instead of sending it
+    match QueryBuilder::<VnQuery>::new().try_filters(r#"["olang", "=", "ja""#) {
+        Ok(_) => println!("valid filters"),
+        Err(error) => eprintln!("{}", error),
+    }
+}
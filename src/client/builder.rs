@@ -0,0 +1,220 @@
+use reqwest::{Client, Proxy};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::client::middleware::Middleware;
+use crate::client::ratelimit::{RateLimit, RateLimiter};
+use crate::client::retry::RetryPolicy;
+use crate::client::{VndbApiClient, API_ENDPOINT};
+use crate::format::error::VndbApiError;
+
+/// User-Agent sent when none is configured, VNDB asks API consumers to identify themselves
+pub const DEFAULT_USER_AGENT: &str = concat!(
+    "vndb-api/",
+    env!("CARGO_PKG_VERSION"),
+    " (+https://github.com/demeil1/vndb-api)"
+);
+
+/// Configures and builds a VndbApiClient
+pub struct VndbApiClientBuilder {
+    /// Base URL of the kana API, defaults to API_ENDPOINT
+    pub base_url: String,
+    /// Token sent with every request, none for a client that only reads public data
+    pub access_token: Option<String>,
+    /// Preconfigured reqwest client, when set the HTTP settings below are ignored
+    pub client: Option<Client>,
+    /// Maximum time allowed to establish a connection
+    pub connect_timeout: Option<Duration>,
+    /// Maximum time allowed for a whole request, from sending it until the response body is read
+    pub timeout: Option<Duration>,
+    pub user_agent: String,
+    /// URL of a proxy all requests are sent through
+    pub proxy: Option<String>,
+    /// Whether gzip compressed responses are requested and decompressed
+    pub gzip: bool,
+    /// Whether brotli compressed responses are requested and decompressed
+    pub brotli: bool,
+    /// Maximum number of idle connections kept open per host
+    pub pool_max_idle_per_host: Option<usize>,
+    /// How long an idle connection is kept open before being closed
+    pub pool_idle_timeout: Option<Duration>,
+    /// Limiter every request waits on, requests are not throttled when unset
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    /// Which failed requests are sent again, defaults to RetryPolicy::none()
+    pub retry_policy: RetryPolicy,
+    /// Middlewares every request passes through, in the order they were added,
+    /// before the retry policy and rate limiter
+    pub middlewares: Vec<Arc<dyn Middleware>>,
+    /// Number of bytes of a response body kept in decode errors, the whole body when unset
+    pub error_body_limit: Option<usize>,
+}
+
+impl VndbApiClientBuilder {
+    pub fn new() -> Self {
+        VndbApiClientBuilder {
+            base_url: API_ENDPOINT.to_string(),
+            access_token: None,
+            client: None,
+            connect_timeout: Some(Duration::from_secs(10)),
+            timeout: Some(Duration::from_secs(30)),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            proxy: None,
+            gzip: true,
+            brotli: true,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout: None,
+            rate_limiter: None,
+            retry_policy: RetryPolicy::none(),
+            middlewares: Vec::new(),
+            error_body_limit: None,
+        }
+    }
+
+    /// Point the client at another kana compatible server
+    /// such as the sandbox, a caching proxy, or a local mock
+    pub fn base_url(mut self, url: &str) -> Self {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn token(mut self, token: &str) -> Self {
+        self.access_token = Some(token.to_string());
+        self
+    }
+
+    /// Use an existing reqwest client instead of creating a new one
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Remove both the connect and request timeouts
+    pub fn no_timeouts(mut self) -> Self {
+        self.connect_timeout = None;
+        self.timeout = None;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    pub fn proxy(mut self, proxy_url: &str) -> Self {
+        self.proxy = Some(proxy_url.to_string());
+        self
+    }
+
+    pub fn gzip(mut self, enable: bool) -> Self {
+        self.gzip = enable;
+        self
+    }
+
+    pub fn brotli(mut self, enable: bool) -> Self {
+        self.brotli = enable;
+        self
+    }
+
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Throttle requests to the given limits, see RateLimit::vndb() for VNDB's budget
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(limit)));
+        self
+    }
+
+    /// Share a limiter between several clients using the same token or IP address
+    pub fn rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Retry rate limited, unavailable and reset requests, see RetryPolicy::new()
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Add a layer around every request, see Middleware
+    ///
+    /// RetryPolicy, RateLimiter and ResponseCache are middlewares too, added here they run in
+    /// the position given instead of after every other middleware
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middlewares.push(Arc::new(middleware));
+        self
+    }
+
+    /// Keep at most `limit` bytes of the response body in decode errors
+    pub fn error_body_limit(mut self, limit: usize) -> Self {
+        self.error_body_limit = Some(limit);
+        self
+    }
+
+    /// Fails when the proxy URL is invalid or the underlying HTTP client cannot be created
+    pub fn build(self) -> Result<VndbApiClient, VndbApiError> {
+        let client = match self.client {
+            Some(client) => client,
+            None => {
+                let mut builder = Client::builder()
+                    .user_agent(self.user_agent)
+                    .gzip(self.gzip)
+                    .brotli(self.brotli);
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if let Some(proxy_url) = self.proxy {
+                    builder = builder.proxy(Proxy::all(proxy_url)?);
+                }
+                if let Some(max) = self.pool_max_idle_per_host {
+                    builder = builder.pool_max_idle_per_host(max);
+                }
+                if let Some(timeout) = self.pool_idle_timeout {
+                    builder = builder.pool_idle_timeout(timeout);
+                }
+                builder.build()?
+            }
+        };
+
+        let mut middlewares = self.middlewares;
+        middlewares.push(Arc::new(self.retry_policy));
+        if let Some(limiter) = &self.rate_limiter {
+            middlewares.push(limiter.clone());
+        }
+
+        Ok(VndbApiClient {
+            client,
+            base_url: self.base_url,
+            access_token: self.access_token,
+            rate_limiter: self.rate_limiter,
+            middlewares: middlewares.into(),
+            error_body_limit: self.error_body_limit,
+        })
+    }
+}
+
+impl Default for VndbApiClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,287 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::client::pager::{Cursor, Pager};
+use crate::client::VndbApiClient;
+use crate::format::character::Character;
+use crate::format::error::VndbApiError;
+use crate::format::producer::Producer;
+use crate::format::release::Release;
+use crate::format::staff::Staff;
+use crate::format::tag::Tag;
+use crate::format::traits::Trait;
+use crate::format::ulist::UList;
+use crate::format::vn::VisualNovel;
+use crate::request::response::Response;
+
+/// Results that can be crawled by id
+pub trait Keyed {
+    /// VNDB id the results are ordered by, e.g. "v17"
+    fn key(&self) -> Option<&str>;
+
+    /// Filter every crawl of these results is limited to, for entries that share an id
+    fn crawl_filter() -> Option<Value> {
+        None
+    }
+}
+
+macro_rules! impl_keyed {
+    ($($t:ty),*) => {
+        $(impl Keyed for $t {
+            fn key(&self) -> Option<&str> {
+                self.id.as_deref()
+            }
+        })*
+    };
+}
+
+impl_keyed!(VisualNovel, Release, Producer, Character, Tag, Trait, UList);
+
+impl Keyed for Staff {
+    fn key(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// The aliases of a staff entry share its id, only its main name can be crawled by id
+    fn crawl_filter() -> Option<Value> {
+        Some(json!(["ismain", "=", 1]))
+    }
+}
+
+/// Position of a crawl that can be saved and passed to CrawlStream::resume()
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+pub struct CrawlCheckpoint {
+    /// Id of the last result yielded, the crawl continues with the ids after it
+    pub last_id: Option<String>,
+}
+
+/// Every result of a query walked in id order using ["id", ">", last_id] instead of page numbers
+///
+/// The query's sort, order and page are replaced, its filters are kept and combined with the id
+/// predicate, which keeps requests cheap for the server no matter how deep the crawl goes
+pub type CrawlStream<'a, T> = Pager<'a, T, IdCursor>;
+
+/// Cursor of a CrawlStream, the id of the last result fetched and of the last one yielded
+pub struct IdCursor {
+    filters: Value,
+    checkpoint: CrawlCheckpoint,
+    fetched: Option<String>,
+}
+
+impl<T: Keyed> Cursor<T> for IdCursor {
+    fn next_body(&self, body: &Value) -> Value {
+        let mut body = body.clone();
+        body["filters"] = match (&self.fetched, &self.filters) {
+            (Some(last_id), Value::Null) => json!(["id", ">", last_id]),
+            (Some(last_id), filters) => json!(["and", filters, ["id", ">", last_id]]),
+            (None, filters) => filters.clone(),
+        };
+        body
+    }
+
+    fn advance(&mut self, response: &Response<T>) -> Result<bool, VndbApiError> {
+        let last = match response.results.last() {
+            Some(last) if response.more => last,
+            _ => return Ok(false),
+        };
+        // without an id to continue from the next request would repeat this one
+        let last_id = last.key().ok_or(VndbApiError::MissingCrawlKey)?;
+        self.fetched = Some(last_id.to_string());
+        Ok(true)
+    }
+
+    fn yielded(&mut self, item: &T) {
+        if let Some(key) = item.key() {
+            self.checkpoint.last_id = Some(key.to_string());
+        }
+    }
+}
+
+impl<'a, T> CrawlStream<'a, T>
+where
+    T: DeserializeOwned + Keyed + Send + 'a,
+{
+    pub(crate) fn new(client: &'a VndbApiClient, url: String, mut body: Value) -> Self {
+        let filters = body["filters"].take();
+        body["sort"] = json!("id");
+        body["reverse"] = json!(false);
+        body["page"] = json!(1);
+        // the id of every result is needed to continue after it
+        body["fields"] = match body["fields"].as_str() {
+            Some(fields) if fields.split(',').any(|field| field == "id") => json!(fields),
+            Some(fields) if !fields.is_empty() => json!(format!("id,{}", fields)),
+            _ => json!("id"),
+        };
+        let cursor = IdCursor {
+            filters,
+            checkpoint: CrawlCheckpoint::default(),
+            fetched: None,
+        };
+        Pager::with_cursor(client, url, body, cursor)
+    }
+
+    /// Only crawl results that also match the given filter
+    pub(crate) fn restrict(mut self, filter: Value) -> Self {
+        self.cursor.filters = match self.cursor.filters.take() {
+            Value::Null => filter,
+            filters => json!(["and", filters, filter]),
+        };
+        self
+    }
+
+    /// Continue a previous crawl after the id stored in the checkpoint
+    pub fn resume(mut self, checkpoint: CrawlCheckpoint) -> Self {
+        self.cursor.fetched = checkpoint.last_id.clone();
+        self.cursor.checkpoint = checkpoint;
+        self
+    }
+
+    /// Position after the last result yielded, save it to resume the crawl later
+    pub fn checkpoint(&self) -> &CrawlCheckpoint {
+        &self.cursor.checkpoint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::schema::Language;
+    use crate::request::fields::VnFields;
+    use crate::request::filter::{StaffFilter, VnFilter};
+    use crate::request::query::{QueryBuilder, StaffQuery, VnQuery, VnSort};
+
+    fn client() -> VndbApiClient {
+        VndbApiClient::anonymous()
+    }
+
+    fn next_body<T: Keyed>(stream: &CrawlStream<'_, T>) -> Value {
+        Cursor::<T>::next_body(&stream.cursor, &stream.body)
+    }
+
+    fn page(ids: &[&str], more: bool) -> Response<VisualNovel> {
+        let results = ids.iter().map(|id| json!({ "id": id })).collect();
+        Response {
+            results: serde_json::from_value(Value::Array(results)).unwrap(),
+            more,
+            count: None,
+            compact_filters: None,
+            normalized_filters: None,
+        }
+    }
+
+    #[test]
+    fn crawls_without_filters() {
+        let client = client();
+        let query = QueryBuilder::<VnQuery>::new().build().unwrap();
+        let mut stream = client.vn_crawl(&query);
+        let body = next_body(&stream);
+        assert_eq!(body["filters"], Value::Null);
+        assert_eq!(body["sort"], "id");
+        assert_eq!(body["fields"], "id");
+
+        assert!(stream.cursor.advance(&page(&["v1", "v2"], true)).unwrap());
+        assert_eq!(next_body(&stream)["filters"], json!(["id", ">", "v2"]));
+    }
+
+    #[test]
+    fn keeps_the_query_filters() {
+        let client = client();
+        let query = QueryBuilder::<VnQuery>::new()
+            .filters(VnFilter::olang().eq(Language::Japanese) & VnFilter::has_anime().set())
+            .build()
+            .unwrap();
+        let filters = serde_json::to_value(&query).unwrap()["filters"].clone();
+        let mut stream = client.vn_crawl(&query);
+        assert_eq!(next_body(&stream)["filters"], filters);
+
+        assert!(stream.cursor.advance(&page(&["v5"], true)).unwrap());
+        assert_eq!(
+            next_body(&stream)["filters"],
+            json!(["and", filters, ["id", ">", "v5"]])
+        );
+    }
+
+    #[test]
+    fn replaces_the_query_order() {
+        let client = client();
+        let query = QueryBuilder::<VnQuery>::new()
+            .sort(VnSort::Rating)
+            .reverse()
+            .page(3)
+            .build()
+            .unwrap();
+        let body = next_body(&client.vn_crawl(&query));
+        assert_eq!(body["sort"], "id");
+        assert_eq!(body["reverse"], false);
+        assert_eq!(body["page"], 1);
+    }
+
+    #[test]
+    fn selects_the_id() {
+        let client = client();
+        let query = QueryBuilder::<VnQuery>::new()
+            .select(VnFields::new().title())
+            .build()
+            .unwrap();
+        assert_eq!(next_body(&client.vn_crawl(&query))["fields"], "id,title");
+
+        let query = QueryBuilder::<VnQuery>::new()
+            .select(VnFields::new().title().id())
+            .build()
+            .unwrap();
+        assert_eq!(next_body(&client.vn_crawl(&query))["fields"], "title,id");
+    }
+
+    #[test]
+    fn stops_after_the_last_page() {
+        let client = client();
+        let query = QueryBuilder::<VnQuery>::new().build().unwrap();
+        let mut stream = client.vn_crawl(&query);
+        assert!(!stream.cursor.advance(&page(&["v1"], false)).unwrap());
+        assert!(!stream.cursor.advance(&page(&[], true)).unwrap());
+
+        let mut missing = page(&["v1"], true);
+        missing.results[0].id = None;
+        assert!(matches!(
+            stream.cursor.advance(&missing),
+            Err(VndbApiError::MissingCrawlKey)
+        ));
+    }
+
+    #[test]
+    fn resumes_from_a_checkpoint() {
+        let client = client();
+        let query = QueryBuilder::<VnQuery>::new().build().unwrap();
+        let mut stream = client.vn_crawl(&query);
+        assert!(stream.cursor.advance(&page(&["v1", "v2"], true)).unwrap());
+        let first: VisualNovel = serde_json::from_value(json!({ "id": "v1" })).unwrap();
+        Cursor::<VisualNovel>::yielded(&mut stream.cursor, &first);
+        let checkpoint = stream.checkpoint().clone();
+        assert_eq!(checkpoint.last_id.as_deref(), Some("v1"));
+
+        // v2 was fetched but not yielded, so the resumed crawl starts after v1
+        let resumed = client.vn_crawl(&query).resume(checkpoint.clone());
+        assert_eq!(next_body(&resumed)["filters"], json!(["id", ">", "v1"]));
+        assert_eq!(resumed.checkpoint(), &checkpoint);
+    }
+
+    #[test]
+    fn crawls_main_staff_names_only() {
+        let client = client();
+        let query = QueryBuilder::<StaffQuery>::new().build().unwrap();
+        let ismain = json!(["ismain", "=", 1]);
+        assert_eq!(next_body(&client.staff_crawl(&query))["filters"], ismain);
+        assert_eq!(next_body(&client.crawl(&query))["filters"], ismain);
+
+        let query = QueryBuilder::<StaffQuery>::new()
+            .filters(StaffFilter::lang().eq(Language::Japanese))
+            .build()
+            .unwrap();
+        let filters = serde_json::to_value(&query).unwrap()["filters"].clone();
+        assert_eq!(
+            next_body(&client.staff_crawl(&query))["filters"],
+            json!(["and", filters, ismain])
+        );
+    }
+}
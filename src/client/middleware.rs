@@ -0,0 +1,571 @@
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use reqwest::{Client, Method, StatusCode, Url};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::format::error::VndbApiError;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Request about to be sent by the client, middlewares may change any part of it
+#[derive(Clone, Debug)]
+pub struct ApiRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+    /// JSON body, None for requests without one
+    pub body: Option<Vec<u8>>,
+    /// Whether sending the request twice has the same effect as sending it once,
+    /// false for ulist_patch and rlist_patch
+    pub idempotent: bool,
+}
+
+impl ApiRequest {
+    /// Whether the request changes a list, i.e. a PATCH or DELETE
+    pub fn is_write(&self) -> bool {
+        self.method == Method::PATCH || self.method == Method::DELETE
+    }
+}
+
+/// Successful response with its whole body
+#[derive(Clone, Debug)]
+pub struct ApiResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+/// Hooks run around every request sent by a VndbApiClient
+///
+/// Middlewares are layered in the order they are added to the builder, the first one sees the
+/// request first and the result last. Unsuccessful statuses reach after_response as errors
+///
+/// Only the hooks are needed to observe or change requests, layers that decide whether and how
+/// often the rest of the chain runs, such as retries, rate limits and caches, override handle()
+pub trait Middleware: Send + Sync {
+    /// Called before the request is passed on, an error is returned without sending it
+    fn before_request(&self, request: &mut ApiRequest) -> Result<(), VndbApiError> {
+        let _ = request;
+        Ok(())
+    }
+
+    /// Called with the result of the rest of the chain and the time it took
+    fn after_response(
+        &self,
+        request: &ApiRequest,
+        result: &Result<ApiResponse, VndbApiError>,
+        elapsed: Duration,
+    ) {
+        let _ = (request, result, elapsed);
+    }
+
+    /// Runs the hooks around the rest of the chain
+    fn handle<'a>(
+        &'a self,
+        mut request: ApiRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<ApiResponse, VndbApiError>> {
+        Box::pin(async move {
+            self.before_request(&mut request)?;
+            let started = Instant::now();
+            let result = next.run(request.clone()).await;
+            self.after_response(&request, &result, started.elapsed());
+            result
+        })
+    }
+}
+
+impl<M: Middleware + ?Sized> Middleware for Arc<M> {
+    fn before_request(&self, request: &mut ApiRequest) -> Result<(), VndbApiError> {
+        (**self).before_request(request)
+    }
+
+    fn after_response(
+        &self,
+        request: &ApiRequest,
+        result: &Result<ApiResponse, VndbApiError>,
+        elapsed: Duration,
+    ) {
+        (**self).after_response(request, result, elapsed)
+    }
+
+    fn handle<'a>(
+        &'a self,
+        request: ApiRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<ApiResponse, VndbApiError>> {
+        (**self).handle(request, next)
+    }
+}
+
+/// Rest of a middleware chain, ending with the HTTP request itself
+#[derive(Clone, Copy)]
+pub struct Next<'a> {
+    client: &'a Client,
+    middlewares: &'a [Arc<dyn Middleware>],
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(client: &'a Client, middlewares: &'a [Arc<dyn Middleware>]) -> Self {
+        Next {
+            client,
+            middlewares,
+        }
+    }
+
+    /// Passes the request to the next middleware, or sends it after the last one
+    pub fn run(self, request: ApiRequest) -> BoxFuture<'a, Result<ApiResponse, VndbApiError>> {
+        match self.middlewares.split_first() {
+            Some((middleware, rest)) => middleware.handle(
+                request,
+                Next {
+                    client: self.client,
+                    middlewares: rest,
+                },
+            ),
+            None => Box::pin(execute(self.client, request)),
+        }
+    }
+}
+
+async fn execute(client: &Client, request: ApiRequest) -> Result<ApiResponse, VndbApiError> {
+    let mut builder = client
+        .request(request.method, request.url)
+        .headers(request.headers);
+    if let Some(body) = request.body {
+        builder = builder.body(body);
+    }
+    let response = builder.send().await?;
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response.bytes().await?.to_vec();
+    if !status.is_success() {
+        return Err(VndbApiError::from_parts(status, &headers, &body));
+    }
+    Ok(ApiResponse {
+        status,
+        headers,
+        body,
+    })
+}
+
+/// Writes a line per request with its method, URL, outcome and duration
+///
+/// Lines go to stderr unless another sink is given, bodies and headers are left out
+/// so tokens never end up in logs
+pub struct RequestLog {
+    sink: Box<dyn Fn(&str) + Send + Sync>,
+    writes_only: bool,
+}
+
+impl RequestLog {
+    pub fn new() -> Self {
+        Self::with_sink(|line| eprintln!("{}", line))
+    }
+
+    /// Send every line to the given function, e.g. to forward it to a logging crate
+    pub fn with_sink(sink: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        RequestLog {
+            sink: Box::new(sink),
+            writes_only: false,
+        }
+    }
+
+    /// Only log list changes, for an audit log of writes
+    pub fn writes_only(mut self) -> Self {
+        self.writes_only = true;
+        self
+    }
+}
+
+impl Default for RequestLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Middleware for RequestLog {
+    fn after_response(
+        &self,
+        request: &ApiRequest,
+        result: &Result<ApiResponse, VndbApiError>,
+        elapsed: Duration,
+    ) {
+        if self.writes_only && !request.is_write() {
+            return;
+        }
+        let outcome = match result {
+            Ok(response) => response.status.to_string(),
+            Err(err) => err.to_string(),
+        };
+        (self.sink)(&format!(
+            "{} {} -> {} in {:?}",
+            request.method, request.url, outcome, elapsed
+        ));
+    }
+}
+
+/// Latency of the requests that went through a Timing middleware
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TimingStats {
+    pub requests: u64,
+    pub failures: u64,
+    pub total: Duration,
+    pub max: Duration,
+    pub last: Option<Duration>,
+}
+
+impl TimingStats {
+    pub fn average(&self) -> Option<Duration> {
+        u32::try_from(self.requests)
+            .ok()
+            .filter(|requests| *requests > 0)
+            .map(|requests| self.total / requests)
+    }
+}
+
+impl fmt::Display for TimingStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} requests, {} failed, average {:?}, max {:?}",
+            self.requests,
+            self.failures,
+            self.average().unwrap_or_default(),
+            self.max
+        )
+    }
+}
+
+/// Measures how long requests take, keep an Arc to read the stats while the client uses it
+#[derive(Debug, Default)]
+pub struct Timing {
+    stats: Mutex<TimingStats>,
+}
+
+impl Timing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stats(&self) -> TimingStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    pub fn reset(&self) {
+        *self.stats.lock().unwrap() = TimingStats::default();
+    }
+}
+
+impl Middleware for Timing {
+    fn after_response(
+        &self,
+        _request: &ApiRequest,
+        result: &Result<ApiResponse, VndbApiError>,
+        elapsed: Duration,
+    ) {
+        let mut stats = self.stats.lock().unwrap();
+        stats.requests += 1;
+        if result.is_err() {
+            stats.failures += 1;
+        }
+        stats.total += elapsed;
+        stats.max = stats.max.max(elapsed);
+        stats.last = Some(elapsed);
+    }
+}
+
+/// Keeps successful responses to reads for a while and answers identical requests with them
+///
+/// Requests are identical when their method, URL, body and token are, so a response to one token
+/// is never given to another or to an anonymous request. List changes are never cached
+pub struct ResponseCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<CacheKey, (Instant, ApiResponse)>>,
+}
+
+type CacheKey = (Method, String, Option<Vec<u8>>, Option<HeaderValue>);
+
+impl ResponseCache {
+    /// Cache of at most 1000 responses kept for ttl
+    pub fn new(ttl: Duration) -> Self {
+        ResponseCache {
+            ttl,
+            max_entries: 1000,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Number of responses kept, the oldest one makes room when it is reached
+    pub fn max_entries(mut self, max: usize) -> Self {
+        self.max_entries = max;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    fn key(request: &ApiRequest) -> CacheKey {
+        (
+            request.method.clone(),
+            request.url.to_string(),
+            request.body.clone(),
+            request.headers.get(AUTHORIZATION).cloned(),
+        )
+    }
+
+    fn get(&self, key: &CacheKey) -> Option<ApiResponse> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((stored, response)) if stored.elapsed() < self.ttl => Some(response.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&self, key: CacheKey, response: ApiResponse) {
+        if self.max_entries == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (stored, _)| stored.elapsed() < self.ttl);
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (stored, _))| *stored)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(key, (Instant::now(), response));
+    }
+}
+
+impl Middleware for ResponseCache {
+    fn handle<'a>(
+        &'a self,
+        request: ApiRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<ApiResponse, VndbApiError>> {
+        Box::pin(async move {
+            if request.is_write() {
+                return next.run(request).await;
+            }
+            let key = Self::key(&request);
+            if let Some(response) = self.get(&key) {
+                return Ok(response);
+            }
+            let response = next.run(request).await?;
+            self.insert(key, response.clone());
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Last layer of a test chain, answers every request without sending it
+    #[derive(Default)]
+    struct Server {
+        requests: AtomicUsize,
+    }
+
+    impl Middleware for Server {
+        fn handle<'a>(
+            &'a self,
+            _request: ApiRequest,
+            _next: Next<'a>,
+        ) -> BoxFuture<'a, Result<ApiResponse, VndbApiError>> {
+            let n = self.requests.fetch_add(1, Ordering::SeqCst) + 1;
+            Box::pin(async move {
+                Ok(ApiResponse {
+                    status: StatusCode::OK,
+                    headers: HeaderMap::new(),
+                    body: n.to_string().into_bytes(),
+                })
+            })
+        }
+    }
+
+    fn request(method: Method, token: Option<&str>) -> ApiRequest {
+        let mut headers = HeaderMap::new();
+        if let Some(token) = token {
+            let value = format!("token {}", token).parse().unwrap();
+            headers.insert(AUTHORIZATION, value);
+        }
+        ApiRequest {
+            method,
+            url: "https://api.vndb.org/kana/ulist".parse().unwrap(),
+            headers,
+            body: Some(br#"{"user":"u1"}"#.to_vec()),
+            idempotent: true,
+        }
+    }
+
+    // never used to send anything, every chain ends with a Server
+    fn client() -> &'static Client {
+        static CLIENT: std::sync::OnceLock<Client> = std::sync::OnceLock::new();
+        CLIENT.get_or_init(Client::new)
+    }
+
+    async fn run(chain: &[Arc<dyn Middleware>], request: ApiRequest) -> String {
+        let response = Next::new(client(), chain).run(request).await.unwrap();
+        String::from_utf8(response.body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn caches_per_token() {
+        let server = Arc::new(Server::default());
+        let chain: Vec<Arc<dyn Middleware>> = vec![
+            Arc::new(ResponseCache::new(Duration::from_secs(60))),
+            server.clone(),
+        ];
+
+        assert_eq!(run(&chain, request(Method::POST, Some("a"))).await, "1");
+        assert_eq!(run(&chain, request(Method::POST, Some("a"))).await, "1");
+        assert_eq!(run(&chain, request(Method::POST, Some("b"))).await, "2");
+        assert_eq!(run(&chain, request(Method::POST, None)).await, "3");
+        assert_eq!(run(&chain, request(Method::POST, None)).await, "3");
+        assert_eq!(run(&chain, request(Method::POST, Some("b"))).await, "2");
+        assert_eq!(server.requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn never_caches_writes() {
+        let server = Arc::new(Server::default());
+        let cache = Arc::new(ResponseCache::new(Duration::from_secs(60)));
+        let chain: Vec<Arc<dyn Middleware>> = vec![cache.clone(), server.clone()];
+
+        assert_eq!(run(&chain, request(Method::PATCH, Some("a"))).await, "1");
+        assert_eq!(run(&chain, request(Method::PATCH, Some("a"))).await, "2");
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn expires_entries() {
+        let server = Arc::new(Server::default());
+        let chain: Vec<Arc<dyn Middleware>> = vec![
+            Arc::new(ResponseCache::new(Duration::from_millis(200))),
+            server.clone(),
+        ];
+
+        assert_eq!(run(&chain, request(Method::POST, None)).await, "1");
+        assert_eq!(run(&chain, request(Method::POST, None)).await, "1");
+        std::thread::sleep(Duration::from_millis(250));
+        assert_eq!(run(&chain, request(Method::POST, None)).await, "2");
+    }
+
+    #[tokio::test]
+    async fn evicts_the_oldest_entry() {
+        let server = Arc::new(Server::default());
+        let cache = Arc::new(ResponseCache::new(Duration::from_secs(60)).max_entries(1));
+        let chain: Vec<Arc<dyn Middleware>> = vec![cache.clone(), server.clone()];
+
+        assert_eq!(run(&chain, request(Method::POST, Some("a"))).await, "1");
+        assert_eq!(run(&chain, request(Method::POST, Some("b"))).await, "2");
+        assert_eq!(cache.len(), 1);
+        assert_eq!(run(&chain, request(Method::POST, Some("a"))).await, "3");
+    }
+
+    /// Records when its hooks run
+    struct Trace {
+        name: &'static str,
+        events: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Middleware for Trace {
+        fn before_request(&self, request: &mut ApiRequest) -> Result<(), VndbApiError> {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("before {}", self.name));
+            request
+                .headers
+                .append("x-trace", self.name.parse().unwrap());
+            Ok(())
+        }
+
+        fn after_response(
+            &self,
+            _request: &ApiRequest,
+            _result: &Result<ApiResponse, VndbApiError>,
+            _elapsed: Duration,
+        ) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("after {}", self.name));
+        }
+    }
+
+    #[tokio::test]
+    async fn runs_layers_in_order() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let trace = |name| -> Arc<dyn Middleware> {
+            Arc::new(Trace {
+                name,
+                events: events.clone(),
+            })
+        };
+        let cache = Arc::new(ResponseCache::new(Duration::from_secs(60)));
+        let chain = vec![
+            trace("outer"),
+            cache.clone(),
+            trace("inner"),
+            Arc::new(Server::default()),
+        ];
+
+        run(&chain, request(Method::POST, None)).await;
+        run(&chain, request(Method::POST, None)).await;
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                "before outer",
+                "before inner",
+                "after inner",
+                "after outer",
+                // the second request is answered by the cache before reaching the inner layer
+                "before outer",
+                "after outer",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn stops_when_a_hook_fails() {
+        struct Reject;
+        impl Middleware for Reject {
+            fn before_request(&self, _request: &mut ApiRequest) -> Result<(), VndbApiError> {
+                Err(VndbApiError::MissingCrawlKey)
+            }
+        }
+        let server = Arc::new(Server::default());
+        let chain: Vec<Arc<dyn Middleware>> = vec![Arc::new(Reject), server.clone()];
+        let result = Next::new(client(), &chain)
+            .run(request(Method::POST, None))
+            .await;
+        assert!(matches!(result, Err(VndbApiError::MissingCrawlKey)));
+        assert_eq!(server.requests.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,443 @@
+pub mod builder;
+pub mod crawl;
+pub mod middleware;
+pub mod pager;
+pub mod ratelimit;
+pub mod retry;
+pub mod session;
+pub mod stream;
+
+use reqwest::{Client, RequestBuilder};
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+use crate::format::error::{DecodeError, VndbApiError};
+use crate::request::endpoint::Endpoint;
+use crate::request::projection::EntityProjection;
+use crate::request::query::{
+    CharacterQuery, ProducerQuery, Query, ReleaseQuery, StaffQuery, TagQuery, TraitQuery,
+    UListQuery, VnQuery,
+};
+use crate::request::response::{RawResponse, Response};
+
+use crate::format::auth::AuthInfo;
+use crate::format::stats::VndbStats;
+use crate::format::user::{UserSearch, UserSearchFields};
+
+use crate::format::character::Character;
+use crate::format::producer::Producer;
+use crate::format::release::Release;
+use crate::format::rlist::RListPatch;
+use crate::format::staff::Staff;
+use crate::format::tag::Tag;
+use crate::format::traits::Trait;
+use crate::format::ulist::{UList, UListLabels, UListLabelsFieldChoices, UListPatch};
+use crate::format::vn::VisualNovel;
+
+pub use builder::VndbApiClientBuilder;
+use crawl::CrawlStream;
+use middleware::{ApiRequest, ApiResponse, Middleware, Next};
+use ratelimit::{RateLimitBudget, RateLimiter};
+use session::VndbSession;
+use stream::ResultStream;
+
+/// Production kana endpoint used when no other base URL is configured
+pub const API_ENDPOINT: &str = "https://api.vndb.org/kana";
+/// Sandbox endpoint for testing and development
+pub const SANDBOX_ENDPOINT: &str = "https://beta.vndb.org/api/kana";
+
+#[derive(Clone)]
+pub struct VndbApiClient {
+    client: Client,
+    base_url: String,
+    access_token: Option<String>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    middlewares: Arc<[Arc<dyn Middleware>]>,
+    error_body_limit: Option<usize>,
+}
+
+impl VndbApiClient {
+    /// Client for the production endpoint authenticated with the given token
+    /// using the default builder settings, panics if the HTTP client cannot be created
+    pub fn new(token: &str) -> Self {
+        VndbApiClientBuilder::new()
+            .token(token)
+            .build()
+            .expect("failed to build the HTTP client")
+    }
+
+    /// Client for the production endpoint without a token, for public data only
+    /// using the default builder settings, panics if the HTTP client cannot be created
+    pub fn anonymous() -> Self {
+        VndbApiClientBuilder::new()
+            .build()
+            .expect("failed to build the HTTP client")
+    }
+
+    /// Start configuring a client, see VndbApiClientBuilder
+    pub fn builder() -> VndbApiClientBuilder {
+        VndbApiClientBuilder::new()
+    }
+
+    /// Base URL every endpoint path is appended to
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Whether requests are sent with a token
+    pub fn has_token(&self) -> bool {
+        self.access_token.is_some()
+    }
+
+    /// Copy of the client that sends no token, for calls that must be made anonymously
+    /// It shares the connection pool and rate limit budget of this client
+    pub fn without_token(&self) -> VndbApiClient {
+        VndbApiClient {
+            access_token: None,
+            ..self.clone()
+        }
+    }
+
+    /// Budget left before requests start waiting, None when no rate limit is configured
+    pub fn rate_limit_remaining(&self) -> Option<RateLimitBudget> {
+        self.rate_limiter
+            .as_ref()
+            .map(|limiter| limiter.remaining())
+    }
+
+    /// Sends a request through the middleware chain, which includes the retry policy and
+    /// rate limiter configured on the builder
+    /// Unsuccessful responses are turned into errors
+    /// The token is sent with every request when one is configured
+    async fn send(
+        &self,
+        request: RequestBuilder,
+        idempotent: bool,
+    ) -> Result<ApiResponse, VndbApiError> {
+        let request = self.with_token(request).build()?;
+        let request = ApiRequest {
+            method: request.method().clone(),
+            url: request.url().clone(),
+            headers: request.headers().clone(),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(<[u8]>::to_vec),
+            idempotent,
+        };
+        Next::new(&self.client, &self.middlewares)
+            .run(request)
+            .await
+    }
+
+    /// Decodes a response body while tracking the path of the value being decoded
+    fn decode<T: DeserializeOwned>(&self, response: &ApiResponse) -> Result<T, VndbApiError> {
+        let deserializer = &mut serde_json::Deserializer::from_slice(&response.body);
+        serde_path_to_error::deserialize(deserializer)
+            .map_err(|err| DecodeError::new(err, &response.body, self.error_body_limit).into())
+    }
+
+    /// Sends a query to its endpoint and reads the results as T
+    async fn post_search<T, E>(&self, q: &Query<E>) -> Result<Response<T>, VndbApiError>
+    where
+        T: DeserializeOwned,
+        E: Endpoint,
+    {
+        let url = format!("{}/{}", self.base_url, E::PATH);
+        let response = self.send(self.client.post(&url).json(q), true).await?;
+        self.decode::<Response<T>>(&response)
+    }
+
+    fn with_token(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.access_token {
+            Some(token) => request.header("Authorization", format!("token {}", token)),
+            None => request,
+        }
+    }
+
+    pub async fn get_stats(&self) -> Result<VndbStats, VndbApiError> {
+        let url = format!("{}/stats", self.base_url);
+        let response = self.send(self.client.get(&url), true).await?;
+        self.decode::<VndbStats>(&response)
+    }
+
+    pub async fn get_auth_info(&self) -> Result<AuthInfo, VndbApiError> {
+        let url = format!("{}/authinfo", self.base_url);
+        let response = self.send(self.client.get(&url), true).await?;
+        self.decode::<AuthInfo>(&response)
+    }
+
+    /// Session for the owner of the token, reading its user id and permissions from authinfo
+    /// Fails with Unauthorized when the token is missing or invalid
+    pub async fn session(&self) -> Result<VndbSession, VndbApiError> {
+        let auth_info = self.get_auth_info().await?;
+        Ok(VndbSession::from_auth_info(self.clone(), auth_info))
+    }
+
+    pub async fn get_user(
+        &self,
+        q: &Vec<String>,
+        fields: &UserSearchFields,
+    ) -> Result<UserSearch, VndbApiError> {
+        let url = format!(
+            "{}/user?q={}&fields={}",
+            self.base_url,
+            q.join("&q="),
+            fields.to_csv()
+        );
+        let response = self.send(self.client.get(&url).json(q), true).await?;
+        self.decode::<UserSearch>(&response)
+    }
+
+    /// Search on any endpoint, the result type follows from the query,
+    /// e.g. Query<VnQuery> returns visual novels
+    pub async fn search<E: Endpoint>(
+        &self,
+        q: &Query<E>,
+    ) -> Result<Response<E::Result>, VndbApiError> {
+        self.post_search(q).await
+    }
+
+    /// Search whose results are left as JSON values, next to the exact bytes sent by the server,
+    /// for fields the crate's structs do not model yet or for storing the payload as it is
+    pub async fn search_raw<E: Endpoint>(&self, q: &Query<E>) -> Result<RawResponse, VndbApiError> {
+        let url = format!("{}/{}", self.base_url, E::PATH);
+        let response = self.send(self.client.post(&url).json(q), true).await?;
+        Ok(RawResponse {
+            response: self.decode(&response)?,
+            body: response.body,
+        })
+    }
+
+    /// Search whose results are read into a type of your own instead of the endpoint's result type,
+    /// e.g. `search_as::<MyVn>(&query)`
+    pub async fn search_as<T: DeserializeOwned>(
+        &self,
+        q: &Query<impl Endpoint>,
+    ) -> Result<Response<T>, VndbApiError> {
+        self.post_search(q).await
+    }
+
+    pub async fn vn_search(
+        &self,
+        q: &Query<VnQuery>,
+    ) -> Result<Response<VisualNovel>, VndbApiError> {
+        self.search(q).await
+    }
+
+    pub async fn release_search(
+        &self,
+        q: &Query<ReleaseQuery>,
+    ) -> Result<Response<Release>, VndbApiError> {
+        self.search(q).await
+    }
+
+    pub async fn producer_search(
+        &self,
+        q: &Query<ProducerQuery>,
+    ) -> Result<Response<Producer>, VndbApiError> {
+        self.search(q).await
+    }
+
+    pub async fn character_search(
+        &self,
+        q: &Query<CharacterQuery>,
+    ) -> Result<Response<Character>, VndbApiError> {
+        self.search(q).await
+    }
+
+    pub async fn staff_search(
+        &self,
+        q: &Query<StaffQuery>,
+    ) -> Result<Response<Staff>, VndbApiError> {
+        self.search(q).await
+    }
+
+    pub async fn tag_search(&self, q: &Query<TagQuery>) -> Result<Response<Tag>, VndbApiError> {
+        self.search(q).await
+    }
+
+    pub async fn trait_search(
+        &self,
+        q: &Query<TraitQuery>,
+    ) -> Result<Response<Trait>, VndbApiError> {
+        self.search(q).await
+    }
+
+    pub async fn ulist(&self, q: &Query<UListQuery>) -> Result<Response<UList>, VndbApiError> {
+        self.search(q).await
+    }
+
+    /// Search whose results are read into a projection struct, its fields replace the query's
+    pub async fn search_projection<P>(
+        &self,
+        q: &Query<P::Query>,
+    ) -> Result<Response<P>, VndbApiError>
+    where
+        P: EntityProjection,
+    {
+        let mut body = serde_json::to_value(q).expect("queries always serialize to JSON");
+        body["fields"] = P::fields().into();
+        let url = format!("{}/{}", self.base_url, <P::Query as Endpoint>::PATH);
+        let response = self.send(self.client.post(&url).json(&body), true).await?;
+        self.decode::<Response<P>>(&response)
+    }
+
+    /// Every result of a query on any endpoint, following `more` across pages
+    pub fn search_stream<'a, E>(&'a self, q: &Query<E>) -> ResultStream<'a, E::Result>
+    where
+        E: Endpoint,
+        E::Result: 'a,
+    {
+        let body = serde_json::to_value(q).expect("queries always serialize to JSON");
+        ResultStream::new(self, format!("{}/{}", self.base_url, E::PATH), body)
+    }
+
+    /// Every result of a query on any endpoint in id order, for crawls deeper than paging allows
+    pub fn crawl<'a, E>(&'a self, q: &Query<E>) -> CrawlStream<'a, E::Result>
+    where
+        E: Endpoint,
+        E::Result: crawl::Keyed + 'a,
+    {
+        let body = serde_json::to_value(q).expect("queries always serialize to JSON");
+        let stream = CrawlStream::new(self, format!("{}/{}", self.base_url, E::PATH), body);
+        match <E::Result as crawl::Keyed>::crawl_filter() {
+            Some(filter) => stream.restrict(filter),
+            None => stream,
+        }
+    }
+
+    /// Every visual novel matching the query, following `more` across pages
+    pub fn vn_search_stream(&self, q: &Query<VnQuery>) -> ResultStream<'_, VisualNovel> {
+        self.search_stream(q)
+    }
+
+    /// Every release matching the query, following `more` across pages
+    pub fn release_search_stream(&self, q: &Query<ReleaseQuery>) -> ResultStream<'_, Release> {
+        self.search_stream(q)
+    }
+
+    /// Every producer matching the query, following `more` across pages
+    pub fn producer_search_stream(&self, q: &Query<ProducerQuery>) -> ResultStream<'_, Producer> {
+        self.search_stream(q)
+    }
+
+    /// Every character matching the query, following `more` across pages
+    pub fn character_search_stream(
+        &self,
+        q: &Query<CharacterQuery>,
+    ) -> ResultStream<'_, Character> {
+        self.search_stream(q)
+    }
+
+    /// Every staff entry matching the query, following `more` across pages
+    pub fn staff_search_stream(&self, q: &Query<StaffQuery>) -> ResultStream<'_, Staff> {
+        self.search_stream(q)
+    }
+
+    /// Every tag matching the query, following `more` across pages
+    pub fn tag_search_stream(&self, q: &Query<TagQuery>) -> ResultStream<'_, Tag> {
+        self.search_stream(q)
+    }
+
+    /// Every trait matching the query, following `more` across pages
+    pub fn trait_search_stream(&self, q: &Query<TraitQuery>) -> ResultStream<'_, Trait> {
+        self.search_stream(q)
+    }
+
+    /// Every entry of the user's list matching the query, following `more` across pages
+    pub fn ulist_stream(&self, q: &Query<UListQuery>) -> ResultStream<'_, UList> {
+        self.search_stream(q)
+    }
+
+    /// Every visual novel matching the query in id order, for crawls deeper than paging allows
+    pub fn vn_crawl(&self, q: &Query<VnQuery>) -> CrawlStream<'_, VisualNovel> {
+        self.crawl(q)
+    }
+
+    /// Every release matching the query in id order
+    pub fn release_crawl(&self, q: &Query<ReleaseQuery>) -> CrawlStream<'_, Release> {
+        self.crawl(q)
+    }
+
+    /// Every producer matching the query in id order
+    pub fn producer_crawl(&self, q: &Query<ProducerQuery>) -> CrawlStream<'_, Producer> {
+        self.crawl(q)
+    }
+
+    /// Every character matching the query in id order
+    pub fn character_crawl(&self, q: &Query<CharacterQuery>) -> CrawlStream<'_, Character> {
+        self.crawl(q)
+    }
+
+    /// Every staff entry matching the query in id order, one result per staff entry
+    /// The aliases of a staff entry share its id and cannot be crawled by id, so only the
+    /// main name of each entry is returned, use staff_search_stream() for every alias
+    pub fn staff_crawl(&self, q: &Query<StaffQuery>) -> CrawlStream<'_, Staff> {
+        self.crawl(q)
+    }
+
+    /// Every tag matching the query in id order
+    pub fn tag_crawl(&self, q: &Query<TagQuery>) -> CrawlStream<'_, Tag> {
+        self.crawl(q)
+    }
+
+    /// Every trait matching the query in id order
+    pub fn trait_crawl(&self, q: &Query<TraitQuery>) -> CrawlStream<'_, Trait> {
+        self.crawl(q)
+    }
+
+    /// Every entry of the user's list matching the query in visual novel id order
+    pub fn ulist_crawl(&self, q: &Query<UListQuery>) -> CrawlStream<'_, UList> {
+        self.crawl(q)
+    }
+
+    pub async fn get_ulist_labels(
+        &self,
+        user: &str,
+        fields: &UListLabelsFieldChoices,
+    ) -> Result<UListLabels, VndbApiError> {
+        let url = format!(
+            "{}/ulist_labels?user={}&fields={}",
+            self.base_url,
+            user,
+            fields.to_csv()
+        );
+        let response = self.send(self.client.get(&url), true).await?;
+        self.decode::<UListLabels>(&response)
+    }
+
+    pub async fn ulist_patch(&self, vn_id: &str, patch: &UListPatch) -> Result<(), VndbApiError> {
+        let url = format!("{}/ulist/{}", self.base_url, vn_id);
+        let request = self
+            .client
+            .patch(&url)
+            .header("Content-Type", "application/json")
+            .json(patch);
+        self.send(request, false).await?;
+        Ok(())
+    }
+
+    pub async fn rlist_patch(&self, r_id: &str, patch: &RListPatch) -> Result<(), VndbApiError> {
+        let url = format!("{}/rlist/{}", self.base_url, r_id);
+        let request = self
+            .client
+            .patch(&url)
+            .header("Content-Type", "application/json")
+            .json(patch);
+        self.send(request, false).await?;
+        Ok(())
+    }
+
+    pub async fn ulist_remove(&self, vn_id: &str) -> Result<(), VndbApiError> {
+        let url = format!("{}/ulist/{}", self.base_url, vn_id);
+        self.send(self.client.delete(&url), true).await?;
+        Ok(())
+    }
+
+    pub async fn rlist_remove(&self, r_id: &str) -> Result<(), VndbApiError> {
+        let url = format!("{}/rlist/{}", self.base_url, r_id);
+        self.send(self.client.delete(&url), true).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use futures_core::Stream;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::client::VndbApiClient;
+use crate::format::error::VndbApiError;
+use crate::request::response::Response;
+
+type PageFuture<'a, T> =
+    Pin<Box<dyn Future<Output = Result<Response<T>, VndbApiError>> + Send + 'a>>;
+
+/// How a Pager moves from one request to the next, e.g. by page number or by id
+pub trait Cursor<T> {
+    /// Body of the next request, built from the body of the query
+    fn next_body(&self, body: &Value) -> Value;
+
+    /// Moves past a page of results, returns whether another page should be requested
+    /// An error is yielded after the results of the page
+    fn advance(&mut self, response: &Response<T>) -> Result<bool, VndbApiError>;
+
+    /// Called with every result as it is yielded
+    fn yielded(&mut self, item: &T) {
+        let _ = item;
+    }
+}
+
+/// Results of a query across several requests, fetched one page at a time as the stream is polled
+///
+/// An error ends the stream after being yielded, see ResultStream and CrawlStream
+pub struct Pager<'a, T, C> {
+    client: &'a VndbApiClient,
+    url: String,
+    pub(crate) body: Value,
+    pub(crate) cursor: C,
+    buffer: VecDeque<T>,
+    pending: Option<PageFuture<'a, T>>,
+    error: Option<VndbApiError>,
+    done: bool,
+}
+
+impl<'a, T, C> Pager<'a, T, C>
+where
+    T: DeserializeOwned + Send + 'a,
+    C: Cursor<T>,
+{
+    pub(crate) fn with_cursor(
+        client: &'a VndbApiClient,
+        url: String,
+        body: Value,
+        cursor: C,
+    ) -> Self {
+        Pager {
+            client,
+            url,
+            body,
+            cursor,
+            buffer: VecDeque::new(),
+            pending: None,
+            error: None,
+            done: false,
+        }
+    }
+
+    /// Number of results requested at once, VNDB rejects more than 100 with a BadRequest error
+    /// Larger pages mean fewer requests for the same results
+    pub fn page_size(mut self, results: u32) -> Self {
+        self.body["results"] = Value::from(results);
+        self
+    }
+
+    /// Next result, the same as StreamExt::next() without importing it
+    pub async fn next(&mut self) -> Option<Result<T, VndbApiError>> {
+        poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+
+    /// Collects up to `max_items` results, no further request is sent once that many are read
+    pub async fn collect_all(mut self, max_items: usize) -> Result<Vec<T>, VndbApiError> {
+        let mut items = Vec::new();
+        while items.len() < max_items {
+            match self.next().await {
+                Some(item) => items.push(item?),
+                None => break,
+            }
+        }
+        Ok(items)
+    }
+
+    fn fetch_page(&self) -> PageFuture<'a, T> {
+        let client = self.client;
+        let url = self.url.clone();
+        let body = self.cursor.next_body(&self.body);
+        Box::pin(async move {
+            let response = client
+                .send(client.client.post(&url).json(&body), true)
+                .await?;
+            client.decode::<Response<T>>(&response)
+        })
+    }
+}
+
+// results are only ever moved out of the buffer, never pinned
+impl<T, C> Unpin for Pager<'_, T, C> {}
+
+impl<'a, T, C> Stream for Pager<'a, T, C>
+where
+    T: DeserializeOwned + Send + 'a,
+    C: Cursor<T>,
+{
+    type Item = Result<T, VndbApiError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(item) = this.buffer.pop_front() {
+                this.cursor.yielded(&item);
+                return Poll::Ready(Some(Ok(item)));
+            }
+            if let Some(error) = this.error.take() {
+                this.done = true;
+                return Poll::Ready(Some(Err(error)));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            let pending = match &mut this.pending {
+                Some(pending) => pending,
+                None => this.pending.insert(this.fetch_page()),
+            };
+            let result = match pending.as_mut().poll(cx) {
+                Poll::Ready(result) => result,
+                Poll::Pending => return Poll::Pending,
+            };
+            this.pending = None;
+
+            match result.and_then(|response| {
+                let more = this.cursor.advance(&response);
+                this.buffer.extend(response.results);
+                more
+            }) {
+                Ok(more) => this.done = !more,
+                Err(error) => this.error = Some(error),
+            }
+        }
+    }
+}
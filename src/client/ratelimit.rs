@@ -0,0 +1,288 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Mutex as AsyncMutex;
+use tokio::time::Instant;
+
+use crate::client::middleware::{ApiRequest, ApiResponse, BoxFuture, Middleware, Next};
+use crate::format::error::VndbApiError;
+
+/// Limits applied by a RateLimiter
+///
+/// VNDB allows around 200 requests per 5 minutes and 1 second of server time per minute,
+/// see https://api.vndb.org/kana#usage-terms
+///
+/// Zero request counts, windows and budgets are raised to 1 request and 1 millisecond
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+    /// Number of requests allowed per window, also the largest burst allowed
+    pub requests: u32,
+    /// Window the request budget refills over
+    pub per: Duration,
+    /// Optional time budget and the window it refills over
+    /// The time a request spends waiting on a response is used as an upper
+    /// bound of the server time it used, which includes network latency
+    pub server_time: Option<(Duration, Duration)>,
+}
+
+impl RateLimit {
+    /// 200 requests per 5 minutes
+    ///
+    /// VNDB's server time budget is left out, the client only sees the whole round trip and
+    /// charging it against 1 second per minute allows a handful of requests on a slow network.
+    /// Add it with server_time() when requests are known to be answered quickly
+    pub fn vndb() -> Self {
+        RateLimit::new(200, Duration::from_secs(300))
+    }
+
+    pub fn new(requests: u32, per: Duration) -> Self {
+        RateLimit {
+            requests,
+            per,
+            server_time: None,
+        }
+    }
+
+    /// Also budget the time spent waiting on responses
+    /// e.g. server_time(Duration::from_secs(1), Duration::from_secs(60))
+    pub fn server_time(mut self, budget: Duration, per: Duration) -> Self {
+        self.server_time = Some((budget, per));
+        self
+    }
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self::vndb()
+    }
+}
+
+/// Budget left in a RateLimiter at a given moment
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitBudget {
+    /// Whole requests that can be sent without waiting
+    pub requests: u32,
+    /// Server time left, None when no server time budget is configured
+    /// Zero when previous requests went over the budget
+    pub server_time: Option<Duration>,
+}
+
+const MIN_DURATION: Duration = Duration::from_millis(1);
+
+struct Bucket {
+    requests: f64,
+    server_time: f64,
+    last_refill: Instant,
+}
+
+/// Token bucket shared by every request sent through a client
+///
+/// Requests wait asynchronously for budget in the order they arrived instead of failing
+pub struct RateLimiter {
+    limit: RateLimit,
+    bucket: Mutex<Bucket>,
+    queue: AsyncMutex<()>,
+}
+
+impl RateLimiter {
+    pub fn new(mut limit: RateLimit) -> Self {
+        // a zero window divides by zero and a zero budget never refills
+        limit.requests = limit.requests.max(1);
+        limit.per = limit.per.max(MIN_DURATION);
+        limit.server_time = limit
+            .server_time
+            .map(|(budget, per)| (budget.max(MIN_DURATION), per.max(MIN_DURATION)));
+        let server_time = limit
+            .server_time
+            .map(|(budget, _)| budget.as_secs_f64())
+            .unwrap_or(0.0);
+        RateLimiter {
+            bucket: Mutex::new(Bucket {
+                requests: limit.requests as f64,
+                server_time,
+                last_refill: Instant::now(),
+            }),
+            limit,
+            queue: AsyncMutex::new(()),
+        }
+    }
+
+    pub fn limit(&self) -> &RateLimit {
+        &self.limit
+    }
+
+    /// Waits until a request can be sent and takes it from the budget
+    pub async fn acquire(&self) {
+        let _turn = self.queue.lock().await;
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap();
+                self.refill(&mut bucket);
+                if bucket.requests >= 1.0 && bucket.server_time >= 0.0 {
+                    bucket.requests -= 1.0;
+                    return;
+                }
+                self.time_until_available(&bucket)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Takes the time a request took from the server time budget
+    pub fn record_server_time(&self, elapsed: Duration) {
+        if self.limit.server_time.is_some() {
+            let mut bucket = self.bucket.lock().unwrap();
+            self.refill(&mut bucket);
+            bucket.server_time -= elapsed.as_secs_f64();
+        }
+    }
+
+    pub fn remaining(&self) -> RateLimitBudget {
+        let mut bucket = self.bucket.lock().unwrap();
+        self.refill(&mut bucket);
+        RateLimitBudget {
+            requests: bucket.requests.floor() as u32,
+            server_time: self
+                .limit
+                .server_time
+                .map(|_| Duration::from_secs_f64(bucket.server_time.max(0.0))),
+        }
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.last_refill = now;
+
+        let capacity = self.limit.requests as f64;
+        bucket.requests = (bucket.requests + elapsed * self.request_rate()).min(capacity);
+        if let Some((budget, per)) = self.limit.server_time {
+            let rate = budget.as_secs_f64() / per.as_secs_f64();
+            bucket.server_time = (bucket.server_time + elapsed * rate).min(budget.as_secs_f64());
+        }
+    }
+
+    fn request_rate(&self) -> f64 {
+        self.limit.requests as f64 / self.limit.per.as_secs_f64()
+    }
+
+    /// Never longer than a full refill so a zero budget cannot wait forever
+    fn time_until_available(&self, bucket: &Bucket) -> Duration {
+        let mut wait = if bucket.requests < 1.0 {
+            ((1.0 - bucket.requests) / self.request_rate()).min(self.limit.per.as_secs_f64())
+        } else {
+            0.0
+        };
+        if let Some((budget, per)) = self.limit.server_time {
+            if bucket.server_time < 0.0 {
+                let rate = budget.as_secs_f64() / per.as_secs_f64();
+                wait = wait.max((-bucket.server_time / rate).min(per.as_secs_f64()));
+            }
+        }
+        Duration::from_secs_f64(wait).max(Duration::from_millis(1))
+    }
+}
+
+impl Middleware for RateLimiter {
+    /// Waits for budget before passing each request on and records the time it took
+    fn handle<'a>(
+        &'a self,
+        request: ApiRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<ApiResponse, VndbApiError>> {
+        Box::pin(async move {
+            self.acquire().await;
+            let started = Instant::now();
+            let result = next.run(request).await;
+            self.record_server_time(started.elapsed());
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Requests the limiter lets through within `window` when each one takes `latency`
+    async fn requests_within(limit: RateLimit, latency: Duration, window: Duration) -> u32 {
+        let limiter = RateLimiter::new(limit);
+        let end = Instant::now() + window;
+        let mut sent = 0;
+        loop {
+            limiter.acquire().await;
+            if Instant::now() >= end {
+                return sent;
+            }
+            tokio::time::sleep(latency).await;
+            limiter.record_server_time(latency);
+            sent += 1;
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn vndb_preset_allows_its_request_count() {
+        let latency = Duration::from_millis(300);
+        let sent = requests_within(RateLimit::vndb(), latency, Duration::from_secs(300)).await;
+        // the whole bucket plus what refills over the window
+        assert!((200..=400).contains(&sent), "{}", sent);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn refills_requests_over_the_window() {
+        let limit = RateLimit::new(10, Duration::from_secs(60));
+        let limiter = RateLimiter::new(limit);
+        let start = Instant::now();
+        for _ in 0..10 {
+            limiter.acquire().await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        assert_eq!(limiter.remaining().requests, 0);
+
+        // one request refills every 6 seconds
+        limiter.acquire().await;
+        let waited = start.elapsed();
+        assert!(waited >= Duration::from_secs(6), "{:?}", waited);
+        assert!(waited < Duration::from_millis(6100), "{:?}", waited);
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert_eq!(limiter.remaining().requests, 10);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_for_server_time() {
+        let limit = RateLimit::new(1000, Duration::from_secs(60))
+            .server_time(Duration::from_secs(1), Duration::from_secs(60));
+        let latency = Duration::from_millis(250);
+        let sent = requests_within(limit, latency, Duration::from_secs(300)).await;
+        // 1 second at the start and 5 more refilled over 5 minutes, 4 requests per second
+        assert!((20..=28).contains(&sent), "{}", sent);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reports_overdrawn_server_time_as_zero() {
+        let limit = RateLimit::new(10, Duration::from_secs(60))
+            .server_time(Duration::from_secs(1), Duration::from_secs(60));
+        let limiter = RateLimiter::new(limit);
+        limiter.acquire().await;
+        limiter.record_server_time(Duration::from_secs(2));
+        assert_eq!(limiter.remaining().server_time, Some(Duration::ZERO));
+
+        // 1 second overdrawn takes a minute to pay back before the next request
+        let start = Instant::now();
+        limiter.acquire().await;
+        assert!(start.elapsed() >= Duration::from_secs(60));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn raises_zero_limits() {
+        let limiter = RateLimiter::new(
+            RateLimit::new(0, Duration::ZERO).server_time(Duration::ZERO, Duration::ZERO),
+        );
+        assert_eq!(limiter.limit().requests, 1);
+        assert_eq!(limiter.limit().per, Duration::from_millis(1));
+        for _ in 0..3 {
+            limiter.acquire().await;
+            limiter.record_server_time(Duration::from_micros(100));
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use std::time::Duration;
+
+use crate::client::middleware::{ApiRequest, ApiResponse, BoxFuture, Middleware, Next};
+use crate::format::error::VndbApiError;
+
+/// Decides which failed requests are sent again and how long to wait in between
+///
+/// Retries happen on errors for which VndbApiError::is_retryable() holds:
+/// 429, 502, 503 and 504 responses and connection resets
+/// The delay doubles with every attempt up to max_delay and is randomly reduced
+/// by up to half so that clients failing together do not retry together
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts including the first one, 1 disables retries
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub base_delay: Duration,
+    /// Upper bound of the delay between two attempts
+    pub max_delay: Duration,
+    /// Whether ulist_patch and rlist_patch may be sent again
+    /// Off by default since those requests are not idempotent
+    pub retry_writes: bool,
+}
+
+impl RetryPolicy {
+    /// 3 attempts starting with a 500ms delay capped at 30s, writes are not retried
+    pub fn new() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            retry_writes: false,
+        }
+    }
+
+    /// Send every request only once
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Self::new()
+        }
+    }
+
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    pub fn retry_writes(mut self, enable: bool) -> Self {
+        self.retry_writes = enable;
+        self
+    }
+
+    /// Number of attempts allowed for a request
+    pub fn attempts_for(&self, idempotent: bool) -> u32 {
+        if idempotent || self.retry_writes {
+            self.max_attempts.max(1)
+        } else {
+            1
+        }
+    }
+
+    /// Delay before attempt number `attempt + 1`, attempts start at 1
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .base_delay
+            .saturating_mul(1u32 << exponent)
+            .min(self.max_delay);
+        delay.mul_f64(0.5 + fastrand::f64() * 0.5)
+    }
+
+    /// Delay before retrying after the given error, honoring the server's Retry-After up to max_delay
+    pub fn delay_for(&self, attempt: u32, error: &VndbApiError) -> Duration {
+        let retry_after = error.retry_after().unwrap_or_default().min(self.max_delay);
+        self.delay(attempt).max(retry_after)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Middleware for RetryPolicy {
+    /// Runs the rest of the chain again on retryable errors, so layers after this one
+    /// see every attempt and layers before it see the final result
+    fn handle<'a>(
+        &'a self,
+        request: ApiRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<ApiResponse, VndbApiError>> {
+        Box::pin(async move {
+            let attempts = self.attempts_for(request.idempotent);
+            let mut attempt = 1;
+            loop {
+                match next.run(request.clone()).await {
+                    Err(error) if attempt < attempts && error.is_retryable() => {
+                        tokio::time::sleep(self.delay_for(attempt, &error)).await;
+                        attempt += 1;
+                    }
+                    result => return result,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderMap;
+    use reqwest::{Client, Method, StatusCode};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use tokio::time::Instant;
+
+    /// Last layer of a test chain, fails every request with the given status
+    struct Failing {
+        status: StatusCode,
+        retry_after: Option<&'static str>,
+        requests: AtomicU32,
+    }
+
+    impl Failing {
+        fn new(status: StatusCode) -> Arc<Self> {
+            Arc::new(Failing {
+                status,
+                retry_after: None,
+                requests: AtomicU32::new(0),
+            })
+        }
+    }
+
+    impl Middleware for Failing {
+        fn handle<'a>(
+            &'a self,
+            _request: ApiRequest,
+            _next: Next<'a>,
+        ) -> BoxFuture<'a, Result<ApiResponse, VndbApiError>> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            let mut headers = HeaderMap::new();
+            if let Some(retry_after) = self.retry_after {
+                headers.insert("retry-after", retry_after.parse().unwrap());
+            }
+            let error = VndbApiError::from_parts(self.status, &headers, b"failed");
+            Box::pin(async move { Err(error) })
+        }
+    }
+
+    fn request(method: Method, idempotent: bool) -> ApiRequest {
+        ApiRequest {
+            method,
+            url: "https://api.vndb.org/kana/ulist/v17".parse().unwrap(),
+            headers: HeaderMap::new(),
+            body: None,
+            idempotent,
+        }
+    }
+
+    /// Runs a request through the policy, returns how many attempts reached the server
+    async fn attempts(policy: RetryPolicy, server: Arc<Failing>, request: ApiRequest) -> u32 {
+        let client = Client::new();
+        let chain: Vec<Arc<dyn Middleware>> = vec![Arc::new(policy), server.clone()];
+        assert!(Next::new(&client, &chain).run(request).await.is_err());
+        server.requests.load(Ordering::SeqCst)
+    }
+
+    #[test]
+    fn doubles_the_delay_up_to_the_cap() {
+        let policy = RetryPolicy::new()
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(1000));
+        let expected = [100, 200, 400, 800, 1000, 1000];
+        for (attempt, full) in (1..).zip(expected) {
+            let full = Duration::from_millis(full);
+            for _ in 0..20 {
+                let delay = policy.delay(attempt);
+                assert!(delay >= full / 2 && delay <= full, "{:?}", delay);
+            }
+        }
+        assert!(policy.delay(u32::MAX) <= Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn honors_retry_after() {
+        let policy = RetryPolicy::new()
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_secs(30));
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", "10".parse().unwrap());
+        let limited = VndbApiError::from_parts(StatusCode::TOO_MANY_REQUESTS, &headers, b"");
+        assert_eq!(policy.delay_for(1, &limited), Duration::from_secs(10));
+
+        headers.insert("retry-after", "3600".parse().unwrap());
+        let limited = VndbApiError::from_parts(StatusCode::TOO_MANY_REQUESTS, &headers, b"");
+        assert_eq!(policy.delay_for(1, &limited), Duration::from_secs(30));
+
+        let unavailable =
+            VndbApiError::from_parts(StatusCode::SERVICE_UNAVAILABLE, &HeaderMap::new(), b"");
+        assert!(policy.delay_for(1, &unavailable) <= Duration::from_millis(100));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_between_attempts() {
+        let server = Arc::new(Failing {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            retry_after: Some("5"),
+            requests: AtomicU32::new(0),
+        });
+        let policy = RetryPolicy::new().base_delay(Duration::from_millis(100));
+        let started = Instant::now();
+        assert_eq!(
+            attempts(policy, server, request(Method::POST, true)).await,
+            3
+        );
+        assert!(started.elapsed() >= Duration::from_secs(10));
+        assert!(started.elapsed() < Duration::from_secs(11));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_only_retryable_errors() {
+        let server = Failing::new(StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            attempts(RetryPolicy::new(), server, request(Method::POST, true)).await,
+            3
+        );
+        let server = Failing::new(StatusCode::BAD_REQUEST);
+        assert_eq!(
+            attempts(RetryPolicy::new(), server, request(Method::POST, true)).await,
+            1
+        );
+        let server = Failing::new(StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            attempts(RetryPolicy::none(), server, request(Method::POST, true)).await,
+            1
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_writes_only_when_enabled() {
+        let server = Failing::new(StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            attempts(RetryPolicy::new(), server, request(Method::PATCH, false)).await,
+            1
+        );
+        let server = Failing::new(StatusCode::SERVICE_UNAVAILABLE);
+        let policy = RetryPolicy::new().retry_writes(true);
+        assert_eq!(
+            attempts(policy, server, request(Method::PATCH, false)).await,
+            3
+        );
+    }
+}
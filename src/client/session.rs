@@ -0,0 +1,117 @@
+use crate::client::crawl::CrawlStream;
+use crate::client::stream::ResultStream;
+use crate::client::VndbApiClient;
+use crate::format::auth::{AuthInfo, ListPermission};
+use crate::format::error::VndbApiError;
+use crate::format::rlist::RListPatch;
+use crate::format::ulist::{UList, UListLabels, UListLabelsFieldChoices, UListPatch};
+use crate::request::query::{Query, UListQuery};
+use crate::request::response::Response;
+
+/// Client bound to the owner of its token, see VndbApiClient::session()
+///
+/// The user id and permissions are read once from authinfo, list queries default to the
+/// token owner and list changes fail locally when the token lacks listwrite
+#[derive(Clone)]
+pub struct VndbSession {
+    client: VndbApiClient,
+    user_id: String,
+    username: String,
+    permissions: Vec<ListPermission>,
+}
+
+impl VndbSession {
+    /// Session from authinfo that was already fetched for the token of the client
+    pub fn from_auth_info(client: VndbApiClient, auth_info: AuthInfo) -> Self {
+        VndbSession {
+            client,
+            user_id: auth_info.id,
+            username: auth_info.username,
+            permissions: auth_info.permissions,
+        }
+    }
+
+    /// Client the session sends its requests with
+    pub fn client(&self) -> &VndbApiClient {
+        &self.client
+    }
+
+    /// Id of the token owner, e.g. "u1"
+    pub fn user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    pub fn permissions(&self) -> &[ListPermission] {
+        &self.permissions
+    }
+
+    pub fn has_permission(&self, permission: &ListPermission) -> bool {
+        self.permissions.contains(permission)
+    }
+
+    fn require(&self, permission: ListPermission) -> Result<(), VndbApiError> {
+        if self.has_permission(&permission) {
+            Ok(())
+        } else {
+            Err(VndbApiError::MissingPermission(permission))
+        }
+    }
+
+    /// The query with its user set to the token owner unless it already names one
+    fn for_owner(&self, q: &Query<UListQuery>) -> Query<UListQuery> {
+        let mut q = q.clone();
+        if q.user.is_none() {
+            q.user = Some(self.user_id.clone());
+        }
+        q
+    }
+
+    /// List of the token owner, or of the user the query names
+    pub async fn my_ulist(&self, q: &Query<UListQuery>) -> Result<Response<UList>, VndbApiError> {
+        self.client.ulist(&self.for_owner(q)).await
+    }
+
+    pub fn my_ulist_stream(&self, q: &Query<UListQuery>) -> ResultStream<'_, UList> {
+        self.client.ulist_stream(&self.for_owner(q))
+    }
+
+    pub fn my_ulist_crawl(&self, q: &Query<UListQuery>) -> CrawlStream<'_, UList> {
+        self.client.ulist_crawl(&self.for_owner(q))
+    }
+
+    /// Labels of the token owner, including private ones when the token has listread
+    pub async fn my_ulist_labels(
+        &self,
+        fields: &UListLabelsFieldChoices,
+    ) -> Result<UListLabels, VndbApiError> {
+        self.client.get_ulist_labels(&self.user_id, fields).await
+    }
+
+    /// Fails with MissingPermission before sending anything when the token lacks listwrite
+    pub async fn ulist_patch(&self, vn_id: &str, patch: &UListPatch) -> Result<(), VndbApiError> {
+        self.require(ListPermission::ListWrite)?;
+        self.client.ulist_patch(vn_id, patch).await
+    }
+
+    /// Fails with MissingPermission before sending anything when the token lacks listwrite
+    pub async fn rlist_patch(&self, r_id: &str, patch: &RListPatch) -> Result<(), VndbApiError> {
+        self.require(ListPermission::ListWrite)?;
+        self.client.rlist_patch(r_id, patch).await
+    }
+
+    /// Fails with MissingPermission before sending anything when the token lacks listwrite
+    pub async fn ulist_remove(&self, vn_id: &str) -> Result<(), VndbApiError> {
+        self.require(ListPermission::ListWrite)?;
+        self.client.ulist_remove(vn_id).await
+    }
+
+    /// Fails with MissingPermission before sending anything when the token lacks listwrite
+    pub async fn rlist_remove(&self, r_id: &str) -> Result<(), VndbApiError> {
+        self.require(ListPermission::ListWrite)?;
+        self.client.rlist_remove(r_id).await
+    }
+}
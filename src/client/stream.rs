@@ -0,0 +1,42 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::client::pager::{Cursor, Pager};
+use crate::client::VndbApiClient;
+use crate::format::error::VndbApiError;
+use crate::request::response::Response;
+
+/// Results of a query across every page, fetched one page at a time as the stream is polled
+///
+/// Starts at the page set in the query and requests the next one while the server reports more
+/// results, an error ends the stream after being yielded
+pub type ResultStream<'a, T> = Pager<'a, T, PageCursor>;
+
+/// Cursor of a ResultStream, the number of the next page
+pub struct PageCursor {
+    page: u64,
+}
+
+impl<T> Cursor<T> for PageCursor {
+    fn next_body(&self, body: &Value) -> Value {
+        let mut body = body.clone();
+        body["page"] = Value::from(self.page);
+        body
+    }
+
+    fn advance(&mut self, response: &Response<T>) -> Result<bool, VndbApiError> {
+        self.page += 1;
+        // an empty page also ends the stream so that results(0) cannot loop forever
+        Ok(response.more && !response.results.is_empty())
+    }
+}
+
+impl<'a, T> ResultStream<'a, T>
+where
+    T: DeserializeOwned + Send + 'a,
+{
+    pub(crate) fn new(client: &'a VndbApiClient, url: String, body: Value) -> Self {
+        let page = body.get("page").and_then(Value::as_u64).unwrap_or(1);
+        Pager::with_cursor(client, url, body, PageCursor { page })
+    }
+}
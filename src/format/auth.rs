@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 /// Validates and returns information about the given API token
 #[derive(Deserialize, Serialize, Debug)]
@@ -9,9 +10,23 @@ pub struct AuthInfo {
     pub permissions: Vec<ListPermission>,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum ListPermission {
     ListRead,
     ListWrite,
+    /// Permission not listed above
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl fmt::Display for ListPermission {
+    /// Name of the permission as used by the API, e.g. "listwrite"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListPermission::ListRead => write!(f, "listread"),
+            ListPermission::ListWrite => write!(f, "listwrite"),
+            ListPermission::Unknown(name) => write!(f, "{}", name),
+        }
+    }
 }
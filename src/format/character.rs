@@ -3,7 +3,6 @@ use crate::format::release::{ExtLink, Release};
 use crate::format::schema::{Language, Platform};
 use crate::format::vn::*;
 use serde::{Deserialize, Serialize};
-use serde_repr::*;
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Character {
@@ -28,6 +27,8 @@ pub struct Character {
     pub age: Option<u32>,
     /// [month, day]
     pub birthday: Option<Vec<u8>>,
+    /// [apparent (non-spoiler) sex, real (spoiler) sex], the latter is null when it is the same
+    pub sex: Option<Vec<Option<CharacterGender>>>,
     /// The character’s apparent (non-spoiler) sex and the character’s real (spoiler) sex
     pub gender: Option<CharacterGenderWrapper>,
     pub vns: Option<Vec<CharacterVnRelation>>,
@@ -60,6 +61,9 @@ pub enum CharacterGender {
     Female,
     #[serde(rename = "b")]
     Both,
+    /// Sex code not listed above
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -82,6 +86,7 @@ pub struct CharacterVnRelation {
     pub length_minutes: Option<u32>,
     pub length_votes: Option<u32>,
     pub description: Option<String>,
+    pub average: Option<f32>,
     pub rating: Option<f32>,
     pub votecount: Option<u32>,
     pub screenshots: Option<Vec<VnScreenShot>>,
@@ -102,6 +107,9 @@ pub enum CharacterRole {
     Primary,
     Side,
     Appears,
+    /// Role not listed above
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -120,10 +128,34 @@ pub struct CharacterTrait {
     pub char_count: Option<u32>,
 }
 
-#[derive(Deserialize_repr, Serialize_repr, PartialEq, Debug)]
-#[repr(u8)]
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(from = "u8", into = "u8")]
 pub enum CharacterTraitSpoiler {
-    None = 0,
-    Medium = 1,
-    Big = 2,
+    None,
+    Medium,
+    Big,
+    /// Spoiler level not listed above
+    Unknown(u8),
+}
+
+impl From<u8> for CharacterTraitSpoiler {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => CharacterTraitSpoiler::None,
+            1 => CharacterTraitSpoiler::Medium,
+            2 => CharacterTraitSpoiler::Big,
+            value => CharacterTraitSpoiler::Unknown(value),
+        }
+    }
+}
+
+impl From<CharacterTraitSpoiler> for u8 {
+    fn from(value: CharacterTraitSpoiler) -> Self {
+        match value {
+            CharacterTraitSpoiler::None => 0,
+            CharacterTraitSpoiler::Medium => 1,
+            CharacterTraitSpoiler::Big => 2,
+            CharacterTraitSpoiler::Unknown(value) => value,
+        }
+    }
 }
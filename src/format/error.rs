@@ -1,35 +1,527 @@
-use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Response, StatusCode};
 use std::error::Error;
 use std::fmt;
+use std::io;
+use std::time::Duration;
 
+use crate::format::auth::ListPermission;
+use crate::request::query::SortField;
+
+/// Errors returned by the VndbApiClient
+///
+/// Variants created from an unsuccessful response keep the message sent by the server
+/// while the others keep the reqwest error that caused them
 #[derive(Debug)]
-pub struct VndbApiError {
-    pub status: Option<StatusCode>,
-    pub message: String,
+pub enum VndbApiError {
+    /// 400, the query is invalid e.g. an unknown filter or field name
+    BadRequest { message: String },
+    /// 401, the token is missing or invalid
+    Unauthorized { message: String },
+    /// 403, the token lacks a permission such as listwrite
+    Forbidden { message: String },
+    /// 404, the requested entry or path does not exist
+    NotFound { message: String },
+    /// 429, too many requests were sent, retry_after comes from the Retry-After header
+    RateLimited {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    /// 5xx, the server failed to handle the request
+    Server { status: StatusCode, message: String },
+    /// Any other unsuccessful status
+    Status { status: StatusCode, message: String },
+    /// The request did not complete within the configured timeout
+    Timeout(reqwest::Error),
+    /// The request could not be sent or the connection failed
+    Network(reqwest::Error),
+    /// The response body is not the JSON the result type expects
+    Decode(DecodeError),
+    /// The client could not be built e.g. because of an invalid proxy URL
+    Build(reqwest::Error),
+    /// The token of a VndbSession lacks a permission the request needs,
+    /// reported before the request is sent
+    MissingPermission(ListPermission),
+    /// A crawled result came without the id the crawl continues after
+    MissingCrawlKey,
 }
 
-impl Error for VndbApiError {}
+impl Error for VndbApiError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            VndbApiError::Timeout(err) | VndbApiError::Network(err) | VndbApiError::Build(err) => {
+                Some(err)
+            }
+            VndbApiError::Decode(err) => Some(err),
+            _ => None,
+        }
+    }
+}
 
 impl fmt::Display for VndbApiError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Error ({:#?}): {:#?}", self.status, self.message)
+        match self {
+            VndbApiError::BadRequest { message } => write!(f, "Bad request: {}", message),
+            VndbApiError::Unauthorized { message } => write!(f, "Unauthorized: {}", message),
+            VndbApiError::Forbidden { message } => write!(f, "Forbidden: {}", message),
+            VndbApiError::NotFound { message } => write!(f, "Not found: {}", message),
+            VndbApiError::RateLimited { message, .. } => write!(f, "Rate limited: {}", message),
+            VndbApiError::Server { status, message } => {
+                write!(f, "Server error ({}): {}", status, message)
+            }
+            VndbApiError::Status { status, message } => {
+                write!(f, "Error ({}): {}", status, message)
+            }
+            VndbApiError::Timeout(err) => write!(f, "Request timed out: {}", err),
+            VndbApiError::Network(err) => write!(f, "Network error: {}", err),
+            VndbApiError::Decode(err) => write!(f, "{}", err),
+            VndbApiError::Build(err) => write!(f, "Could not build the client: {}", err),
+            VndbApiError::MissingPermission(permission) => {
+                write!(f, "The token lacks the {} permission", permission)
+            }
+            VndbApiError::MissingCrawlKey => {
+                write!(f, "A crawled result has no id to continue the crawl after")
+            }
+        }
     }
 }
 
 impl VndbApiError {
+    /// Classifies an unsuccessful status and the message sent with it
     pub fn new(status: StatusCode, message: String) -> Self {
-        Self {
-            status: Some(status),
-            message,
+        match status {
+            StatusCode::BAD_REQUEST => VndbApiError::BadRequest { message },
+            StatusCode::UNAUTHORIZED => VndbApiError::Unauthorized { message },
+            StatusCode::FORBIDDEN => VndbApiError::Forbidden { message },
+            StatusCode::NOT_FOUND => VndbApiError::NotFound { message },
+            StatusCode::TOO_MANY_REQUESTS => VndbApiError::RateLimited {
+                message,
+                retry_after: None,
+            },
+            status if status.is_server_error() => VndbApiError::Server { status, message },
+            status => VndbApiError::Status { status, message },
+        }
+    }
+
+    /// Reads the message and Retry-After header of an unsuccessful response
+    pub async fn from_response(response: Response) -> Self {
+        let status = response.status();
+        let headers = response.headers().clone();
+        match response.bytes().await {
+            Ok(body) => VndbApiError::from_parts(status, &headers, &body),
+            Err(err) => err.into(),
+        }
+    }
+
+    /// Classifies an unsuccessful response from its status, headers and body
+    pub fn from_parts(status: StatusCode, headers: &HeaderMap, body: &[u8]) -> Self {
+        let retry_after = headers
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        let message = String::from_utf8_lossy(body).into_owned();
+
+        match VndbApiError::new(status, message) {
+            VndbApiError::RateLimited { message, .. } => VndbApiError::RateLimited {
+                message,
+                retry_after,
+            },
+            error => error,
+        }
+    }
+
+    /// HTTP status of the response that caused the error if there was one
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            VndbApiError::BadRequest { .. } => Some(StatusCode::BAD_REQUEST),
+            VndbApiError::Unauthorized { .. } => Some(StatusCode::UNAUTHORIZED),
+            VndbApiError::Forbidden { .. } => Some(StatusCode::FORBIDDEN),
+            VndbApiError::NotFound { .. } => Some(StatusCode::NOT_FOUND),
+            VndbApiError::RateLimited { .. } => Some(StatusCode::TOO_MANY_REQUESTS),
+            VndbApiError::Server { status, .. } | VndbApiError::Status { status, .. } => {
+                Some(*status)
+            }
+            VndbApiError::Timeout(err) | VndbApiError::Network(err) | VndbApiError::Build(err) => {
+                err.status()
+            }
+            VndbApiError::Decode(_)
+            | VndbApiError::MissingPermission(_)
+            | VndbApiError::MissingCrawlKey => None,
+        }
+    }
+
+    /// Whether sending the same request again may succeed:
+    /// rate limits, 502, 503 and 504 responses, and reset connections
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            VndbApiError::RateLimited { .. } => true,
+            VndbApiError::Server { status, .. } => matches!(
+                *status,
+                StatusCode::BAD_GATEWAY
+                    | StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::GATEWAY_TIMEOUT
+            ),
+            VndbApiError::Network(err) => is_connection_reset(err),
+            _ => false,
+        }
+    }
+
+    /// How long the server asked to wait before sending another request
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            VndbApiError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
         }
     }
 }
 
+impl From<DecodeError> for VndbApiError {
+    fn from(err: DecodeError) -> Self {
+        VndbApiError::Decode(err)
+    }
+}
+
 impl From<reqwest::Error> for VndbApiError {
     fn from(err: reqwest::Error) -> Self {
-        let status = err.status();
-        let message = format!("Reqwest error: {}", err);
+        if err.is_timeout() {
+            VndbApiError::Timeout(err)
+        } else if err.is_builder() {
+            VndbApiError::Build(err)
+        } else {
+            VndbApiError::Network(err)
+        }
+    }
+}
+
+/// A response body that does not match the structs it is decoded into
+#[derive(Debug)]
+pub struct DecodeError {
+    /// Path of the value that failed e.g. results[3].releases[0].resolution
+    pub path: String,
+    /// Raw response body, cut short when it exceeds the client's error body limit
+    pub body: String,
+    /// Whether body was cut short
+    pub truncated: bool,
+    /// Error reported by serde_json
+    pub inner: serde_json::Error,
+}
+
+impl DecodeError {
+    /// Keeps at most `limit` bytes of the body, or all of it when there is no limit
+    pub fn new(
+        error: serde_path_to_error::Error<serde_json::Error>,
+        body: &[u8],
+        limit: Option<usize>,
+    ) -> Self {
+        let path = error.path().to_string();
+        let body = String::from_utf8_lossy(body);
+        let (body, truncated) = match limit {
+            Some(limit) if body.len() > limit => {
+                let mut end = limit;
+                while !body.is_char_boundary(end) {
+                    end -= 1;
+                }
+                (body[..end].to_string(), true)
+            }
+            _ => (body.into_owned(), false),
+        };
+
+        DecodeError {
+            path,
+            body,
+            truncated,
+            inner: error.into_inner(),
+        }
+    }
+}
+
+impl Error for DecodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.inner)
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Could not decode the response at {}: {}",
+            self.path, self.inner
+        )
+    }
+}
+
+fn is_connection_reset(err: &reqwest::Error) -> bool {
+    let mut source = err.source();
+    while let Some(err) = source {
+        if let Some(io_error) = err.downcast_ref::<io::Error>() {
+            if matches!(
+                io_error.kind(),
+                io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::BrokenPipe
+            ) {
+                return true;
+            }
+        }
+        source = err.source();
+    }
+    false
+}
+
+/// A query VNDB would reject, found before it is sent
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    /// Every problem found in the query
+    pub violations: Vec<QueryViolation>,
+}
+
+/// A single problem found in a query
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryViolation {
+    /// More than 100 results per page were requested
+    TooManyResults(u32),
+    /// Page numbers start at 1
+    PageZero,
+    /// The endpoint cannot sort on this field
+    UnsupportedSort(SortField),
+    /// Sorting on Searchrank needs a search filter
+    SearchrankWithoutSearch,
+}
+
+impl Error for QueryError {}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid query: ")?;
+        for (i, violation) in self.violations.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for QueryViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryViolation::TooManyResults(results) => {
+                write!(
+                    f,
+                    "{} results per page requested, at most 100 are allowed",
+                    results
+                )
+            }
+            QueryViolation::PageZero => write!(f, "page numbers start at 1"),
+            QueryViolation::UnsupportedSort(sort) => {
+                write!(f, "results of this endpoint cannot be sorted on {:?}", sort)
+            }
+            QueryViolation::SearchrankWithoutSearch => {
+                write!(f, "sorting on searchrank requires a search filter")
+            }
+        }
+    }
+}
+
+/// Filters that could not be read
+#[derive(Debug)]
+pub enum FilterError {
+    /// The filters are not valid JSON
+    Json(serde_json::Error),
+    /// The JSON does not have the shape of a filter
+    Malformed(String),
+    /// A compact filter string could not be read or written
+    Compact(String),
+    /// A text filter query could not be parsed, position counts characters from 0
+    Syntax { position: usize, message: String },
+}
+
+impl Error for FilterError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FilterError::Json(err) => Some(err),
+            FilterError::Malformed(_) | FilterError::Compact(_) | FilterError::Syntax { .. } => {
+                None
+            }
+        }
+    }
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterError::Json(err) => write!(f, "Invalid filter JSON: {}", err),
+            FilterError::Malformed(message) => write!(f, "Invalid filter: {}", message),
+            FilterError::Compact(message) => write!(f, "Invalid compact filter: {}", message),
+            FilterError::Syntax { position, message } => {
+                write!(
+                    f,
+                    "Invalid filter query at position {}: {}",
+                    position, message
+                )
+            }
+        }
+    }
+}
+
+impl From<serde_json::Error> for FilterError {
+    fn from(err: serde_json::Error) -> Self {
+        FilterError::Json(err)
+    }
+}
+
+/// A vndb.org URL that could not be turned into a query or the other way around
+#[derive(Debug)]
+pub enum BrowseUrlError {
+    /// The text is not a vndb.org URL
+    InvalidUrl(String),
+    /// The URL lists another type of entry, e.g. a release search given for a vn query
+    WrongPath { expected: String, found: String },
+    /// The s parameter names a sort order this kind of query does not support
+    UnknownSort(String),
+    /// A parameter such as the page number has an invalid value
+    InvalidParam { name: String, value: String },
+    /// The f parameter or the query filters could not be converted
+    Filter(FilterError),
+}
+
+impl Error for BrowseUrlError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BrowseUrlError::Filter(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BrowseUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BrowseUrlError::InvalidUrl(url) => write!(f, "Not a vndb.org URL: {}", url),
+            BrowseUrlError::WrongPath { expected, found } => {
+                write!(f, "Expected a {} URL, found {}", expected, found)
+            }
+            BrowseUrlError::UnknownSort(sort) => write!(f, "Unknown sort order: {}", sort),
+            BrowseUrlError::InvalidParam { name, value } => {
+                write!(f, "Invalid value for the {} parameter: {}", name, value)
+            }
+            BrowseUrlError::Filter(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<FilterError> for BrowseUrlError {
+    fn from(err: FilterError) -> Self {
+        BrowseUrlError::Filter(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::vn::VisualNovel;
+    use crate::request::response::Response;
+
+    fn kind(error: &VndbApiError) -> &'static str {
+        match error {
+            VndbApiError::BadRequest { .. } => "BadRequest",
+            VndbApiError::Unauthorized { .. } => "Unauthorized",
+            VndbApiError::Forbidden { .. } => "Forbidden",
+            VndbApiError::NotFound { .. } => "NotFound",
+            VndbApiError::RateLimited { .. } => "RateLimited",
+            VndbApiError::Server { .. } => "Server",
+            VndbApiError::Status { .. } => "Status",
+            _ => "other",
+        }
+    }
+
+    #[test]
+    fn classifies_responses() {
+        let cases = [
+            (400, None, "BadRequest", false, None),
+            (401, None, "Unauthorized", false, None),
+            (403, None, "Forbidden", false, None),
+            (404, None, "NotFound", false, None),
+            (429, None, "RateLimited", true, None),
+            (429, Some("12"), "RateLimited", true, Some(12)),
+            (429, Some(" 3 "), "RateLimited", true, Some(3)),
+            (
+                429,
+                Some("Wed, 21 Oct 2026 07:28:00 GMT"),
+                "RateLimited",
+                true,
+                None,
+            ),
+            (500, Some("12"), "Server", false, None),
+            (502, None, "Server", true, None),
+            (503, None, "Server", true, None),
+            (504, None, "Server", true, None),
+            (505, None, "Server", false, None),
+            (302, None, "Status", false, None),
+            (409, None, "Status", false, None),
+            (418, None, "Status", false, None),
+        ];
+        for (status, retry_after, expected, retryable, seconds) in cases {
+            let status = StatusCode::from_u16(status).unwrap();
+            let mut headers = HeaderMap::new();
+            if let Some(retry_after) = retry_after {
+                headers.insert(RETRY_AFTER, retry_after.parse().unwrap());
+            }
+            let error = VndbApiError::from_parts(status, &headers, b"message");
+            assert_eq!(kind(&error), expected, "{}", status);
+            assert_eq!(error.status(), Some(status));
+            assert_eq!(error.is_retryable(), retryable, "{}", status);
+            assert_eq!(error.retry_after(), seconds.map(Duration::from_secs));
+            assert!(error.to_string().ends_with("message"), "{}", error);
+        }
+    }
+
+    fn decode_error(body: &[u8], limit: Option<usize>) -> DecodeError {
+        let deserializer = &mut serde_json::Deserializer::from_slice(body);
+        let error = serde_path_to_error::deserialize::<_, Response<VisualNovel>>(deserializer)
+            .map(|_| ())
+            .unwrap_err();
+        DecodeError::new(error, body, limit)
+    }
+
+    #[test]
+    fn reports_the_failing_path() {
+        let body =
+            br#"{"results": [{"id": "v1"}, {"id": "v2", "titles": [{"lang": 7}]}], "more": false}"#;
+        let error = decode_error(body, None);
+        assert_eq!(error.path, "results[1].titles[0].lang");
+        assert!(error
+            .to_string()
+            .starts_with("Could not decode the response at results[1].titles[0].lang: "));
+        assert_eq!(error.body.as_bytes(), body);
+        assert!(!error.truncated);
+    }
+
+    #[test]
+    fn truncates_the_body() {
+        let body = br#"{"results": 7, "more": false}"#;
+        let error = decode_error(body, Some(12));
+        assert_eq!(error.path, "results");
+        assert_eq!(error.body, r#"{"results": "#);
+        assert!(error.truncated);
+
+        let error = decode_error(body, Some(body.len()));
+        assert_eq!(error.body.as_bytes(), body);
+        assert!(!error.truncated);
+    }
+
+    #[test]
+    fn truncates_at_a_character_boundary() {
+        // "é" takes two bytes, a limit of 14 falls between them
+        let body = r#"{"results": "é", "more": false}"#.as_bytes();
+        let error = decode_error(body, Some(14));
+        assert_eq!(error.body, r#"{"results": ""#);
+        assert!(error.truncated);
 
-        Self { status, message }
+        let error = decode_error(body, Some(15));
+        assert_eq!(error.body, r#"{"results": "é"#);
     }
 }
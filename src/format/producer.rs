@@ -6,6 +6,8 @@ pub struct Producer {
     /// Vndbid
     pub id: Option<String>,
     pub name: Option<String>,
+    /// Name in the original script
+    pub original: Option<String>,
     pub aliases: Option<Vec<String>>,
     /// Primary langauge
     pub lang: Option<Language>,
@@ -23,4 +25,7 @@ pub enum ProducerType {
     Individual,
     #[serde(rename = "ng")]
     AmateurGroup,
+    /// Producer type not listed above
+    #[serde(untagged)]
+    Unknown(String),
 }
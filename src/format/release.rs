@@ -2,7 +2,6 @@ use crate::format::producer::*;
 use crate::format::schema::{Language, Medium, Platform};
 use crate::format::vn::*;
 use serde::{Deserialize, Serialize};
-use serde_repr::*;
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Release {
@@ -15,6 +14,8 @@ pub struct Release {
     /// Languages this release is available in
     pub languages: Option<Vec<ReleaseLanguage>>,
     pub platforms: Option<Vec<Platform>>,
+    /// Returned by VNDB as media
+    #[serde(alias = "media")]
     pub medium: Option<Vec<ReleaseMedia>>,
     /// List of visual novels this release is linked to
     pub vns: Option<Vec<ReleaseVnRelation>>,
@@ -84,6 +85,7 @@ pub struct ReleaseVnRelation {
     pub length_minutes: Option<u32>,
     pub length_votes: Option<u32>,
     pub description: Option<String>,
+    pub average: Option<f32>,
     pub rating: Option<f32>,
     pub votecount: Option<u32>,
     pub screenshots: Option<Vec<VnScreenShot>>,
@@ -102,6 +104,9 @@ pub enum ReleaseType {
     Trial,
     Partial,
     Complete,
+    /// Release type not listed above
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -111,6 +116,8 @@ pub struct ReleaseProducer {
     /// All /producer fields are also available
     pub id: Option<String>,
     pub name: Option<String>,
+    /// Name in the original script
+    pub original: Option<String>,
     pub aliases: Option<Vec<String>>,
     pub lang: Option<Language>,
     pub r#type: Option<ProducerType>,
@@ -124,13 +131,39 @@ pub enum Resolution {
     Dimensions(Vec<u32>),
 }
 
-#[derive(Deserialize_repr, Serialize_repr, PartialEq, Debug)]
-#[repr(u8)]
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(from = "u8", into = "u8")]
 pub enum VoicedType {
-    NotVoiced = 1,
-    EroScenesOnly = 2,
-    PartiallyVoiced = 3,
-    FullyVoiced = 4,
+    NotVoiced,
+    EroScenesOnly,
+    PartiallyVoiced,
+    FullyVoiced,
+    /// Value not listed above
+    Unknown(u8),
+}
+
+impl From<u8> for VoicedType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => VoicedType::NotVoiced,
+            2 => VoicedType::EroScenesOnly,
+            3 => VoicedType::PartiallyVoiced,
+            4 => VoicedType::FullyVoiced,
+            value => VoicedType::Unknown(value),
+        }
+    }
+}
+
+impl From<VoicedType> for u8 {
+    fn from(value: VoicedType) -> Self {
+        match value {
+            VoicedType::NotVoiced => 1,
+            VoicedType::EroScenesOnly => 2,
+            VoicedType::PartiallyVoiced => 3,
+            VoicedType::FullyVoiced => 4,
+            VoicedType::Unknown(value) => value,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -18,3 +18,9 @@ impl RListPatch {
         self
     }
 }
+
+impl Default for RListPatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
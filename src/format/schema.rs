@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 #[derive(Deserialize, Serialize, Debug)]
 pub enum Language {
@@ -106,6 +107,71 @@ pub enum Language {
     Urdu,
     #[serde(rename = "vi")]
     Vietnamese,
+    /// Language code not listed above, e.g. one added to VNDB after this release
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl fmt::Display for Language {
+    /// Name of the language in English, the code itself when it is not listed
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Language::Arabic => "Arabic",
+            Language::Basque => "Basque",
+            Language::Belarusian => "Belarusian",
+            Language::Bulgarian => "Bulgarian",
+            Language::Catalan => "Catalan",
+            Language::Cherokee => "Cherokee",
+            Language::Chinese => "Chinese",
+            Language::ChineseSimplified => "Chinese (simplified)",
+            Language::ChineseTraditional => "Chinese (traditional)",
+            Language::Croatian => "Croatian",
+            Language::Czech => "Czech",
+            Language::Danish => "Danish",
+            Language::Dutch => "Dutch",
+            Language::English => "English",
+            Language::Esperanto => "Esperanto",
+            Language::Finnish => "Finnish",
+            Language::French => "French",
+            Language::German => "German",
+            Language::Greek => "Greek",
+            Language::Hebrew => "Hebrew",
+            Language::Hindi => "Hindi",
+            Language::Hungarian => "Hungarian",
+            Language::Irish => "Irish",
+            Language::Indonesian => "Indonesian",
+            Language::Italian => "Italian",
+            Language::Inuktitut => "Inuktitut",
+            Language::Japanese => "Japanese",
+            Language::Korean => "Korean",
+            Language::Latin => "Latin",
+            Language::Latvian => "Latvian",
+            Language::Lithuanian => "Lithuanian",
+            Language::Macedonian => "Macedonian",
+            Language::Malay => "Malay",
+            Language::Norwegian => "Norwegian",
+            Language::Persian => "Persian",
+            Language::Polish => "Polish",
+            Language::PortugueseBrazil => "Portuguese (Brazil)",
+            Language::PortuguesePortugal => "Portuguese (Portugal)",
+            Language::Romanian => "Romanian",
+            Language::Russian => "Russian",
+            Language::ScottishGaelic => "Scottish Gaelic",
+            Language::Serbian => "Serbian",
+            Language::Slovak => "Slovak",
+            Language::Slovene => "Slovene",
+            Language::Spanish => "Spanish",
+            Language::Swedish => "Swedish",
+            Language::Tagalog => "Tagalog",
+            Language::Thai => "Thai",
+            Language::Turkish => "Turkish",
+            Language::Ukrainian => "Ukrainian",
+            Language::Urdu => "Urdu",
+            Language::Vietnamese => "Vietnamese",
+            Language::Unknown(code) => code,
+        };
+        f.write_str(name)
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -136,6 +202,9 @@ pub enum Medium {
     DownloadCard,
     #[serde(rename = "otc")]
     Other,
+    /// Medium code not listed above
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -156,6 +225,9 @@ pub enum StaffRole {
     /// Quality Assurance
     Qa,
     Staff,
+    /// Role not listed above
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -255,4 +327,79 @@ pub enum Platform {
     OtherMobile,
     #[serde(rename = "oth")]
     Other,
+    /// Platform code not listed above
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl fmt::Display for Platform {
+    /// Name of the platform in English, the code itself when it is not listed
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Platform::Windows => "Windows",
+            Platform::Linux => "Linux",
+            Platform::MacOs => "Mac OS",
+            Platform::Website => "Website",
+            Platform::ThreeDO => "3DO",
+            Platform::Apple => "Apple iProduct",
+            Platform::Android => "Android",
+            Platform::BluRayPlayer => "Blu-ray Player",
+            Platform::Dos => "DOS",
+            Platform::DvdPlayer => "DVD Player",
+            Platform::Dreamcast => "Dreamcast",
+            Platform::Famicom => "Famicom",
+            Platform::SuperFamicom => "Super Famicom",
+            Platform::Fm7 => "FM-7",
+            Platform::Fm8 => "FM-8",
+            Platform::FMTowns => "FM Towns",
+            Platform::GameBoyAdvance => "Game Boy Advance",
+            Platform::GameBoyColor => "Game Boy Color",
+            Platform::Msx => "MSX",
+            Platform::NintendoDS => "Nintendo DS",
+            Platform::NintendoSwitch => "Nintendo Switch",
+            Platform::NintendoWii => "Nintendo Wii",
+            Platform::NintendoWiiU => "Nintendo Wii U",
+            Platform::Nintendo3DS => "Nintendo 3DS",
+            Platform::Pc88 => "PC-88",
+            Platform::Pc98 => "PC-98",
+            Platform::PcEngine => "PC Engine",
+            Platform::PcFX => "PC-FX",
+            Platform::PlayStationPortable => "PlayStation Portable",
+            Platform::PlayStation1 => "PlayStation 1",
+            Platform::PlayStation2 => "PlayStation 2",
+            Platform::PlayStation3 => "PlayStation 3",
+            Platform::PlayStation4 => "PlayStation 4",
+            Platform::PlayStation5 => "PlayStation 5",
+            Platform::PlayStationVita => "PlayStation Vita",
+            Platform::SegaMegaDrive => "Sega Mega Drive",
+            Platform::SegaMegaCD => "Sega Mega-CD",
+            Platform::SegaSaturn => "Sega Saturn",
+            Platform::Vnds => "VNDS",
+            Platform::SharpX1 => "Sharp X1",
+            Platform::SharpX68000 => "Sharp X68000",
+            Platform::Xbox => "Xbox",
+            Platform::Xbox360 => "Xbox 360",
+            Platform::XboxOne => "Xbox One",
+            Platform::XboxX => "Xbox X/S",
+            Platform::OtherMobile => "Other (mobile)",
+            Platform::Other => "Other",
+            Platform::Unknown(code) => code,
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_unknown_language_codes() {
+        let language: Language = serde_json::from_str(r#""xx""#).unwrap();
+        assert!(matches!(&language, Language::Unknown(code) if code == "xx"));
+        assert_eq!(serde_json::to_string(&language).unwrap(), r#""xx""#);
+
+        let language: Language = serde_json::from_str(r#""ja""#).unwrap();
+        assert!(matches!(language, Language::Japanese));
+    }
 }
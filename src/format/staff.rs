@@ -33,6 +33,9 @@ pub enum StaffGender {
     Male,
     #[serde(rename = "f")]
     Female,
+    /// Gender code not listed above
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 #[derive(Deserialize, Serialize, Debug)]
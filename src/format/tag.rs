@@ -1,5 +1,4 @@
 use serde::{Deserialize, Serialize};
-use serde_repr::*;
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Tag {
@@ -23,12 +22,39 @@ pub enum TagFlag {
     SexualContent,
     #[serde(rename = "tech")]
     Technical,
+    /// Category not listed above
+    #[serde(untagged)]
+    Unknown(String),
 }
 
-#[derive(Deserialize_repr, Serialize_repr, PartialEq, Debug)]
-#[repr(u8)]
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(from = "u8", into = "u8")]
 pub enum TagSpoilerLvl {
-    None = 0,
-    Medium = 1,
-    Big = 2,
+    None,
+    Medium,
+    Big,
+    /// Spoiler level not listed above
+    Unknown(u8),
+}
+
+impl From<u8> for TagSpoilerLvl {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => TagSpoilerLvl::None,
+            1 => TagSpoilerLvl::Medium,
+            2 => TagSpoilerLvl::Big,
+            value => TagSpoilerLvl::Unknown(value),
+        }
+    }
+}
+
+impl From<TagSpoilerLvl> for u8 {
+    fn from(value: TagSpoilerLvl) -> Self {
+        match value {
+            TagSpoilerLvl::None => 0,
+            TagSpoilerLvl::Medium => 1,
+            TagSpoilerLvl::Big => 2,
+            TagSpoilerLvl::Unknown(value) => value,
+        }
+    }
 }
@@ -1,5 +1,4 @@
 use serde::{Deserialize, Serialize};
-use serde_repr::*;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
@@ -64,14 +63,42 @@ pub struct UListRelease {
     pub extlinks: Option<Vec<ExtLink>>,
 }
 
-#[derive(Deserialize_repr, Serialize_repr, PartialEq, Debug)]
-#[repr(u8)]
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(from = "u8", into = "u8")]
 pub enum UListStatus {
-    Unknown = 0,
-    Pending = 1,
-    Obtained = 2,
-    OnLoan = 3,
-    Deleted = 4,
+    Unknown,
+    Pending,
+    Obtained,
+    OnLoan,
+    Deleted,
+    /// Status not listed above, not named Unknown since VNDB has an unknown status of its own
+    Other(u8),
+}
+
+impl From<u8> for UListStatus {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => UListStatus::Unknown,
+            1 => UListStatus::Pending,
+            2 => UListStatus::Obtained,
+            3 => UListStatus::OnLoan,
+            4 => UListStatus::Deleted,
+            value => UListStatus::Other(value),
+        }
+    }
+}
+
+impl From<UListStatus> for u8 {
+    fn from(value: UListStatus) -> Self {
+        match value {
+            UListStatus::Unknown => 0,
+            UListStatus::Pending => 1,
+            UListStatus::Obtained => 2,
+            UListStatus::OnLoan => 3,
+            UListStatus::Deleted => 4,
+            UListStatus::Other(value) => value,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -123,6 +150,12 @@ impl UListLabelsFieldChoices {
     }
 }
 
+impl Default for UListLabelsFieldChoices {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct UListPatch {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -157,15 +190,49 @@ impl Date {
     }
 }
 
-#[derive(Deserialize_repr, Serialize_repr, PartialEq, Debug)]
-#[repr(u8)]
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(from = "u32", into = "u32")]
 pub enum LabelId {
-    Playing = 1,
-    Finished = 2,
-    Stalled = 3,
-    Dropped = 4,
-    WishList = 5,
-    BlackList = 6,
+    Playing,
+    Finished,
+    Stalled,
+    Dropped,
+    WishList,
+    BlackList,
+    /// Added by VNDB to entries with a vote, setting or unsetting it has no effect
+    Voted,
+    /// Custom label created by the user
+    Unknown(u32),
+}
+
+impl From<u32> for LabelId {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => LabelId::Playing,
+            2 => LabelId::Finished,
+            3 => LabelId::Stalled,
+            4 => LabelId::Dropped,
+            5 => LabelId::WishList,
+            6 => LabelId::BlackList,
+            7 => LabelId::Voted,
+            value => LabelId::Unknown(value),
+        }
+    }
+}
+
+impl From<LabelId> for u32 {
+    fn from(value: LabelId) -> Self {
+        match value {
+            LabelId::Playing => 1,
+            LabelId::Finished => 2,
+            LabelId::Stalled => 3,
+            LabelId::Dropped => 4,
+            LabelId::WishList => 5,
+            LabelId::BlackList => 6,
+            LabelId::Voted => 7,
+            LabelId::Unknown(value) => value,
+        }
+    }
 }
 
 pub struct UListPatchBuilder {
@@ -238,3 +305,9 @@ impl UListPatchBuilder {
         }
     }
 }
+
+impl Default for UListPatchBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -58,3 +58,9 @@ impl UserSearchFields {
             .join(",")
     }
 }
+
+impl Default for UserSearchFields {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -5,7 +5,6 @@ use crate::format::schema::{Language, Platform, StaffRole};
 use crate::format::staff::*;
 use crate::format::tag::*;
 use serde::{Deserialize, Serialize};
-use serde_repr::*;
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct VisualNovel {
@@ -34,6 +33,8 @@ pub struct VisualNovel {
     pub length_votes: Option<u32>,
     /// May contain formatting codes
     pub description: Option<String>,
+    /// Raw vote average from 10 to 100
+    pub average: Option<f32>,
     /// The rating of a VN from 10 to 100
     pub rating: Option<f32>,
     /// Number of votes
@@ -71,12 +72,36 @@ pub struct VnTitle {
     pub main: Option<bool>,
 }
 
-#[derive(Deserialize_repr, Serialize_repr, PartialEq, Debug)]
-#[repr(u8)]
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(from = "u8", into = "u8")]
 pub enum VnDevStatus {
-    Finished = 0,
-    InDevelopment = 1,
-    Cancelled = 2,
+    Finished,
+    InDevelopment,
+    Cancelled,
+    /// Status not listed above
+    Unknown(u8),
+}
+
+impl From<u8> for VnDevStatus {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => VnDevStatus::Finished,
+            1 => VnDevStatus::InDevelopment,
+            2 => VnDevStatus::Cancelled,
+            value => VnDevStatus::Unknown(value),
+        }
+    }
+}
+
+impl From<VnDevStatus> for u8 {
+    fn from(value: VnDevStatus) -> Self {
+        match value {
+            VnDevStatus::Finished => 0,
+            VnDevStatus::InDevelopment => 1,
+            VnDevStatus::Cancelled => 2,
+            VnDevStatus::Unknown(value) => value,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -97,14 +122,42 @@ pub struct VnImage {
     pub thumbnail_dims: Option<Vec<u32>>,
 }
 
-#[derive(Deserialize_repr, Serialize_repr, PartialEq, Debug)]
-#[repr(u8)]
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(from = "u8", into = "u8")]
 pub enum VnLength {
-    VeryShort = 1,
-    Short = 2,
-    Average = 3,
-    Long = 4,
-    VeryLong = 5,
+    VeryShort,
+    Short,
+    Average,
+    Long,
+    VeryLong,
+    /// Length not listed above
+    Unknown(u8),
+}
+
+impl From<u8> for VnLength {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => VnLength::VeryShort,
+            2 => VnLength::Short,
+            3 => VnLength::Average,
+            4 => VnLength::Long,
+            5 => VnLength::VeryLong,
+            value => VnLength::Unknown(value),
+        }
+    }
+}
+
+impl From<VnLength> for u8 {
+    fn from(value: VnLength) -> Self {
+        match value {
+            VnLength::VeryShort => 1,
+            VnLength::Short => 2,
+            VnLength::Average => 3,
+            VnLength::Long => 4,
+            VnLength::VeryLong => 5,
+            VnLength::Unknown(value) => value,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -143,6 +196,7 @@ pub struct VnRelation {
     pub length_minutes: Option<u32>,
     pub length_votes: Option<u32>,
     pub description: Option<String>,
+    pub average: Option<f32>,
     pub rating: Option<f32>,
     pub votecount: Option<u32>,
     pub screenshots: Option<Vec<VnScreenShot>>,
@@ -169,6 +223,7 @@ pub struct VnTag {
     pub description: Option<String>,
     pub category: Option<TagFlag>,
     pub searchable: Option<bool>,
+    pub applicable: Option<bool>,
     pub vn_count: Option<u32>,
 }
 
@@ -211,3 +266,18 @@ pub struct VnVoiceActor {
     pub staff: Option<Staff>,
     pub character: Option<Character>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_unknown_dev_statuses() {
+        let status: VnDevStatus = serde_json::from_str("7").unwrap();
+        assert_eq!(status, VnDevStatus::Unknown(7));
+        assert_eq!(serde_json::to_string(&status).unwrap(), "7");
+
+        let status: VnDevStatus = serde_json::from_str("2").unwrap();
+        assert_eq!(status, VnDevStatus::Cancelled);
+    }
+}
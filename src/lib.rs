@@ -1,3 +1,7 @@
 pub mod client;
 pub mod format;
 pub mod request;
+
+// lets the ::vndb_api paths generated by the derive macros resolve in this crate's tests
+#[cfg(test)]
+extern crate self as vndb_api;
@@ -0,0 +1,315 @@
+use reqwest::Url;
+
+use crate::format::error::BrowseUrlError;
+use crate::request::filter::compact::{decode, encode};
+use crate::request::filter::{Filter, FilterEntity, Operator};
+use crate::request::query::{
+    CharacterQuery, ProducerQuery, Query, QueryBuilder, ReleaseQuery, SortField, StaffQuery,
+    VnQuery,
+};
+
+const BASE_URL: &str = "https://vndb.org/";
+const HOSTS: [&str; 2] = ["vndb.org", "www.vndb.org"];
+
+/// Number of results vndb.org shows on each page of a search
+pub const BROWSE_RESULTS: u32 = 50;
+
+/// Query markers of the entry types vndb.org has an advanced search page for
+pub trait BrowseEntity: FilterEntity {
+    /// Path of the search page, e.g. "v" for https://vndb.org/v
+    const PATH: &'static str;
+    /// Sort orders of the API and the value of the s parameter standing for them on vndb.org
+    const SORTS: &'static [(SortField, &'static str)];
+}
+
+impl BrowseEntity for VnQuery {
+    const PATH: &'static str = "v";
+    const SORTS: &'static [(SortField, &'static str)] = &[
+        (SortField::Searchrank, "qscore"),
+        (SortField::Title, "title"),
+        (SortField::Released, "rel"),
+        (SortField::Rating, "rating"),
+        (SortField::Votecount, "votes"),
+    ];
+}
+
+impl BrowseEntity for ReleaseQuery {
+    const PATH: &'static str = "r";
+    const SORTS: &'static [(SortField, &'static str)] = &[
+        (SortField::Searchrank, "qscore"),
+        (SortField::Title, "title"),
+        (SortField::Released, "released"),
+    ];
+}
+
+impl BrowseEntity for ProducerQuery {
+    const PATH: &'static str = "p";
+    const SORTS: &'static [(SortField, &'static str)] =
+        &[(SortField::Searchrank, "qscore"), (SortField::Name, "name")];
+}
+
+impl BrowseEntity for StaffQuery {
+    const PATH: &'static str = "s";
+    const SORTS: &'static [(SortField, &'static str)] =
+        &[(SortField::Searchrank, "qscore"), (SortField::Name, "name")];
+}
+
+impl BrowseEntity for CharacterQuery {
+    const PATH: &'static str = "c";
+    const SORTS: &'static [(SortField, &'static str)] =
+        &[(SortField::Searchrank, "qscore"), (SortField::Name, "name")];
+}
+
+impl<T: BrowseEntity> QueryBuilder<T> {
+    /// Query of a search made on vndb.org
+    ///
+    /// Reads the search text in q, the compact filters in f, the sort order in s and o and the
+    /// page in p, the results per page are set to the 50 vndb.org shows so pages line up
+    ///
+    /// Returns a builder rather than a Query so that fields and other settings can be added
+    /// before build()
+    ///
+    /// ```
+    /// use vndb_api::request::query::{QueryBuilder, VnQuery};
+    ///
+    /// let url = "https://vndb.org/v?q=fate&f=023gjaa2Nv&s=rating&o=d";
+    /// let query = QueryBuilder::<VnQuery>::from_browse_url(url)
+    ///     .expect("invalid vndb.org URL")
+    ///     .build()
+    ///     .expect("invalid query");
+    /// assert_eq!(query.browse_url().unwrap(), url);
+    /// ```
+    pub fn from_browse_url(url: &str) -> Result<Self, BrowseUrlError> {
+        let base = Url::parse(BASE_URL).expect("the base URL is valid");
+        let parsed = base
+            .join(url)
+            .map_err(|_| BrowseUrlError::InvalidUrl(url.to_string()))?;
+        if !parsed.host_str().is_some_and(|host| HOSTS.contains(&host)) {
+            return Err(BrowseUrlError::InvalidUrl(url.to_string()));
+        }
+        let path = parsed.path().trim_matches('/');
+        if path != T::PATH {
+            return Err(BrowseUrlError::WrongPath {
+                expected: format!("/{}", T::PATH),
+                found: format!("/{}", path),
+            });
+        }
+
+        let mut builder = QueryBuilder::<T>::new().results(BROWSE_RESULTS);
+        let mut search = None;
+        let mut filters = Vec::new();
+        for (name, value) in parsed.query_pairs() {
+            match &*name {
+                "f" if !value.is_empty() => filters.push(decode(T::ENTITY, &value)?),
+                "q" if !value.trim().is_empty() => {
+                    search = Some(Filter::eq("search", value.trim()))
+                }
+                "s" => {
+                    let sort = T::SORTS
+                        .iter()
+                        .find(|(_, param)| *param == value)
+                        .ok_or_else(|| BrowseUrlError::UnknownSort(value.to_string()))?;
+                    builder.sort = Some(sort.0);
+                }
+                "o" => {
+                    builder.reverse = match &*value {
+                        "a" => Some(false),
+                        "d" => Some(true),
+                        _ => return Err(invalid_param("o", &value)),
+                    }
+                }
+                "p" => {
+                    let page = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|page| *page > 0)
+                        .ok_or_else(|| invalid_param("p", &value))?;
+                    builder = builder.page(page);
+                }
+                _ => {}
+            }
+        }
+        // the search goes first, where browse_url() looks for it
+        filters.splice(0..0, search);
+        builder.filters = match filters.len() {
+            0 => None,
+            1 => filters.pop().map(|filter| filter.to_value()),
+            _ => Some(Filter::and(filters).to_value()),
+        };
+        Ok(builder)
+    }
+}
+
+impl<T: BrowseEntity> Query<T> {
+    /// Link to the same search on vndb.org
+    ///
+    /// A search at the top level of the filters, or among the filters of a top level and, is
+    /// written as q and the rest as f. Sort orders vndb.org does not offer, such as id, are left
+    /// out and the site's default is used, the page is kept as is although vndb.org always
+    /// shows 50 results per page
+    pub fn browse_url(&self) -> Result<String, BrowseUrlError> {
+        let mut params = Vec::new();
+        if let Some(filters) = &self.filters {
+            let (search, rest) = split_search(Filter::from_value(filters)?);
+            if let Some(search) = search {
+                params.push(("q", search));
+            }
+            if let Some(rest) = rest {
+                params.push(("f", encode(T::ENTITY, &rest)?));
+            }
+        }
+        let sort = self
+            .sort
+            .and_then(|sort| T::SORTS.iter().find(|(field, _)| *field == sort));
+        if let Some((_, param)) = sort {
+            params.push(("s", param.to_string()));
+            let order = if self.reverse == Some(true) { "d" } else { "a" };
+            params.push(("o", order.to_string()));
+        }
+        if self.page() > 1 {
+            params.push(("p", self.page().to_string()));
+        }
+
+        let mut url = Url::parse(BASE_URL)
+            .and_then(|base| base.join(T::PATH))
+            .expect("the base URL is valid");
+        if !params.is_empty() {
+            url.query_pairs_mut().extend_pairs(params);
+        }
+        Ok(url.to_string())
+    }
+}
+
+/// Text of a search that vndb.org can show in its search box, and the other filters
+fn split_search(filter: Filter) -> (Option<String>, Option<Filter>) {
+    let search = |filter: &Filter| match filter {
+        Filter::Predicate { field, op, value } if field == "search" && *op == Operator::Eq => {
+            value.as_str().map(str::to_string)
+        }
+        _ => None,
+    };
+    match filter {
+        Filter::And(mut filters) => match filters.iter().position(|f| search(f).is_some()) {
+            Some(index) => {
+                let text = search(&filters.remove(index));
+                let rest = match filters.len() {
+                    0 => None,
+                    1 => filters.pop(),
+                    _ => Some(Filter::And(filters)),
+                };
+                (text, rest)
+            }
+            None => (None, Some(Filter::And(filters))),
+        },
+        filter => match search(&filter) {
+            Some(text) => (Some(text), None),
+            None => (None, Some(filter)),
+        },
+    }
+}
+
+fn invalid_param(name: &str, value: &str) -> BrowseUrlError {
+    BrowseUrlError::InvalidParam {
+        name: name.to_string(),
+        value: value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::query::Sortable;
+
+    fn round_trip<T: BrowseEntity + Sortable>(url: &str) -> String {
+        QueryBuilder::<T>::from_browse_url(url)
+            .unwrap()
+            .build()
+            .unwrap()
+            .browse_url()
+            .unwrap()
+    }
+
+    #[test]
+    fn keeps_the_search_text_in_q() {
+        for url in [
+            "https://vndb.org/v?q=fate",
+            "https://vndb.org/v?q=fate+stay+night&f=023gjaa2Nv",
+        ] {
+            assert_eq!(round_trip::<VnQuery>(url), url);
+        }
+        let url = "https://vndb.org/c?q=saber&s=name&o=a";
+        assert_eq!(round_trip::<CharacterQuery>(url), url);
+        let query = QueryBuilder::<VnQuery>::from_browse_url("https://vndb.org/v?q=fate")
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.filters,
+            Some(serde_json::json!(["search", "=", "fate"]))
+        );
+    }
+
+    #[test]
+    fn maps_every_sort_order() {
+        fn check<T: BrowseEntity + Sortable>() {
+            for (field, param) in <T as BrowseEntity>::SORTS {
+                let url = format!("https://vndb.org/{}?q=x&s={}&o=d", T::PATH, param);
+                let builder = QueryBuilder::<T>::from_browse_url(&url).unwrap();
+                assert_eq!(builder.sort, Some(*field), "{}", url);
+                assert_eq!(builder.reverse, Some(true));
+                assert_eq!(builder.build().unwrap().browse_url().unwrap(), url);
+            }
+        }
+        check::<VnQuery>();
+        check::<ReleaseQuery>();
+        check::<ProducerQuery>();
+        check::<StaffQuery>();
+        check::<CharacterQuery>();
+
+        let rel = QueryBuilder::<VnQuery>::from_browse_url("/v?s=rel&o=a").unwrap();
+        assert_eq!(
+            (rel.sort, rel.reverse),
+            (Some(SortField::Released), Some(false))
+        );
+        let votes = QueryBuilder::<VnQuery>::from_browse_url("/v?s=votes&o=d").unwrap();
+        assert_eq!(votes.sort, Some(SortField::Votecount));
+        assert!(matches!(
+            QueryBuilder::<ReleaseQuery>::from_browse_url("/r?s=votes"),
+            Err(BrowseUrlError::UnknownSort(_))
+        ));
+    }
+
+    #[test]
+    fn accepts_vndb_hosts_only() {
+        for url in [
+            "https://vndb.org/v?p=2",
+            "https://www.vndb.org/v?p=2",
+            "http://vndb.org/v/?p=2",
+            "/v?p=2",
+        ] {
+            let builder = QueryBuilder::<VnQuery>::from_browse_url(url).unwrap();
+            assert_eq!(builder.page, Some(2), "{}", url);
+            assert_eq!(builder.results, Some(BROWSE_RESULTS));
+        }
+        for url in ["https://vndb.example/v", "https://beta.vndb.org/v"] {
+            assert!(matches!(
+                QueryBuilder::<VnQuery>::from_browse_url(url),
+                Err(BrowseUrlError::InvalidUrl(_))
+            ));
+        }
+        assert!(matches!(
+            QueryBuilder::<VnQuery>::from_browse_url("https://vndb.org/r"),
+            Err(BrowseUrlError::WrongPath { .. })
+        ));
+        assert!(matches!(
+            QueryBuilder::<VnQuery>::from_browse_url("https://vndb.org/v?p=0"),
+            Err(BrowseUrlError::InvalidParam { .. })
+        ));
+    }
+
+    #[test]
+    fn leaves_out_sorts_the_site_lacks() {
+        let query = QueryBuilder::<VnQuery>::new().results(50).build().unwrap();
+        assert_eq!(query.browse_url().unwrap(), "https://vndb.org/v");
+    }
+}
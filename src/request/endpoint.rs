@@ -0,0 +1,48 @@
+use serde::de::DeserializeOwned;
+
+use crate::format::character::Character;
+use crate::format::producer::Producer;
+use crate::format::release::Release;
+use crate::format::staff::Staff;
+use crate::format::tag::Tag;
+use crate::format::traits::Trait;
+use crate::format::ulist::UList;
+use crate::format::vn::VisualNovel;
+use crate::request::query::{
+    CharacterField, CharacterQuery, ProducerField, ProducerQuery, ReleaseField, ReleaseQuery,
+    Sortable, StaffField, StaffQuery, TagField, TagQuery, TraitField, TraitQuery, UListField,
+    UListQuery, VnField, VnQuery,
+};
+
+/// Query markers of the search endpoints, used by VndbApiClient::search()
+///
+/// The sort enum of the endpoint comes from Sortable, e.g. `<VnQuery as Sortable>::Sort` is VnSort
+pub trait Endpoint: Sortable {
+    /// Path of the endpoint, e.g. "vn"
+    const PATH: &'static str;
+    /// Type each result is read into
+    type Result: DeserializeOwned + Send;
+    /// Fields that can be selected, e.g. VnField
+    type Field;
+}
+
+macro_rules! impl_endpoint {
+    ($($marker:ty => $path:literal, $result:ty, $field:ty;)*) => {
+        $(impl Endpoint for $marker {
+            const PATH: &'static str = $path;
+            type Result = $result;
+            type Field = $field;
+        })*
+    };
+}
+
+impl_endpoint! {
+    VnQuery => "vn", VisualNovel, VnField;
+    ReleaseQuery => "release", Release, ReleaseField;
+    ProducerQuery => "producer", Producer, ProducerField;
+    CharacterQuery => "character", Character, CharacterField;
+    StaffQuery => "staff", Staff, StaffField;
+    TagQuery => "tag", Tag, TagField;
+    TraitQuery => "trait", Trait, TraitField;
+    UListQuery => "ulist", UList, UListField;
+}
@@ -0,0 +1,491 @@
+use std::fmt;
+
+use crate::request::query::{
+    CharacterQuery, ProducerQuery, QueryBuilder, ReleaseQuery, StaffQuery, VnQuery,
+};
+
+/// Name of a field in the API, the method name unless another one is given
+macro_rules! field_name {
+    ($method:ident) => {
+        stringify!($method)
+    };
+    ($method:ident, $name:literal) => {
+        $name
+    };
+}
+
+/// Declares a field selection tree whose methods select plain fields or, given a closure,
+/// the fields of a nested object under its dotted path
+macro_rules! field_tree {
+    (
+        $(#[$doc:meta])*
+        $tree:ident {
+            $($field:ident $(= $field_name:literal)?),* $(,)?
+        }
+        $(nested {
+            $($nested:ident: $nested_tree:ident),* $(,)?
+        })?
+    ) => {
+        $(#[$doc])*
+        #[derive(Clone, Debug, Default, PartialEq)]
+        pub struct $tree {
+            paths: Vec<String>,
+        }
+
+        impl $tree {
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// Dotted paths of every selected field, e.g. ["title", "relations.title"]
+            pub fn paths(&self) -> &[String] {
+                &self.paths
+            }
+
+            /// Names of every field the tree can select, for checking them against the structs
+            /// the response is read into
+            #[cfg(test)]
+            const NAMES: &'static [&'static str] = &[
+                $(field_name!($field $(, $field_name)?),)*
+                $($(stringify!($nested),)*)?
+            ];
+
+            fn push(mut self, path: String) -> Self {
+                if !self.paths.contains(&path) {
+                    self.paths.push(path);
+                }
+                self
+            }
+
+            $(
+                pub fn $field(self) -> Self {
+                    self.push(field_name!($field $(, $field_name)?).to_string())
+                }
+            )*
+
+            $($(
+                pub fn $nested(self, select: impl FnOnce($nested_tree) -> $nested_tree) -> Self {
+                    select($nested_tree::new())
+                        .paths
+                        .into_iter()
+                        .fold(self, |tree, path| {
+                            tree.push(format!("{}.{}", stringify!($nested), path))
+                        })
+                }
+            )*)?
+        }
+
+        impl fmt::Display for $tree {
+            /// Comma separated paths as sent in the fields of a query
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.paths.join(","))
+            }
+        }
+    };
+}
+
+/// Field tree of an object holding every visual novel field next to some of its own
+macro_rules! vn_field_tree {
+    (
+        $(#[$doc:meta])*
+        $tree:ident {
+            $($field:ident),* $(,)?
+        }
+        nested {
+            $($nested:ident: $nested_tree:ident),* $(,)?
+        }
+    ) => {
+        field_tree! {
+            $(#[$doc])*
+            $tree {
+                $($field,)*
+                id, title, alttitle, aliases, olang, devstatus, released, languages, platforms,
+                length, length_minutes, length_votes, description, average, rating, votecount,
+            }
+            nested {
+                $($nested: $nested_tree,)*
+                titles: VnTitleFields,
+                image: ImageFields,
+                screenshots: ScreenshotFields,
+                relations: VnRelationFields,
+                tags: VnTagFields,
+                developers: ProducerFields,
+                editions: VnEditionFields,
+                staff: VnStaffFields,
+                va: VnVoiceActorFields,
+                extlinks: ExtLinkFields,
+            }
+        }
+    };
+}
+
+vn_field_tree! {
+    /// Fields of a visual novel, see https://api.vndb.org/kana#vn-fields
+    ///
+    /// VnFields::new().title().relations(|r| r.relation().title().developers(|d| d.name()))
+    /// selects "title,relations.relation,relations.title,relations.developers.name"
+    VnFields {}
+    nested {}
+}
+
+vn_field_tree! {
+    /// Fields of a related visual novel, read into VnRelation
+    VnRelationFields {
+        relation, relation_official,
+    }
+    nested {}
+}
+
+field_tree! {
+    /// Fields of a visual novel title, read into VnTitle
+    VnTitleFields {
+        lang, title, latin, official, main,
+    }
+}
+
+field_tree! {
+    /// Fields of a visual novel cover image, read into VnImage
+    ImageFields {
+        id, url, dims, sexual, violence, votecount, thumbnail, thumbnail_dims,
+    }
+}
+
+field_tree! {
+    /// Fields of a screenshot, read into VnScreenShot
+    ScreenshotFields {
+        id, url, dims, sexual, violence, votecount, thumbnail, thumbnail_dims,
+    }
+    nested {
+        release: ReleaseFields,
+    }
+}
+
+field_tree! {
+    /// Fields of a tag applied to a visual novel, read into VnTag
+    VnTagFields {
+        rating, spoiler, lie, id, name, aliases, description, category, searchable, applicable,
+        vn_count,
+    }
+}
+
+field_tree! {
+    /// Fields of an edition, read into VnEdition
+    VnEditionFields {
+        eid, lang, name, official,
+    }
+}
+
+field_tree! {
+    /// Fields of a staff member credited on a visual novel, read into VnStaff
+    VnStaffFields {
+        eid, role, note, id, aid, ismain, name, original, lang, gender, description,
+    }
+    nested {
+        extlinks: ExtLinkFields,
+        aliases: StaffAliasFields,
+    }
+}
+
+field_tree! {
+    /// Fields of a voice actor credit, read into VnVoiceActor
+    VnVoiceActorFields {
+        note,
+    }
+    nested {
+        staff: StaffFields,
+        character: CharacterFields,
+    }
+}
+
+field_tree! {
+    /// Fields of an external link, read into ExtLink
+    ExtLinkFields {
+        url, label, name, id,
+    }
+}
+
+field_tree! {
+    /// Fields of a producer, see https://api.vndb.org/kana#producer-fields
+    ProducerFields {
+        id, name, original, aliases, lang, type_ = "type", description,
+    }
+}
+
+field_tree! {
+    /// Fields of a staff member, see https://api.vndb.org/kana#staff-fields
+    StaffFields {
+        id, aid, ismain, name, original, lang, gender, description,
+    }
+    nested {
+        extlinks: ExtLinkFields,
+        aliases: StaffAliasFields,
+    }
+}
+
+field_tree! {
+    /// Fields of a staff alias, read into StaffAlias
+    StaffAliasFields {
+        aid, name, latin, ismain,
+    }
+}
+
+field_tree! {
+    /// Fields of a release, see https://api.vndb.org/kana#release-fields
+    ReleaseFields {
+        id, title, alttitle, platforms, released, minage, patch, freeware, uncensored, official,
+        has_ero, resolution, engine, voiced, notes, gtin, catalog,
+    }
+    nested {
+        languages: ReleaseLanguageFields,
+        media: ReleaseMediaFields,
+        vns: ReleaseVnFields,
+        producers: ReleaseProducerFields,
+        extlinks: ExtLinkFields,
+    }
+}
+
+field_tree! {
+    /// Fields of a release language, read into ReleaseLanguage
+    ReleaseLanguageFields {
+        lang, title, latin, mtl, main,
+    }
+}
+
+field_tree! {
+    /// Fields of a release medium, read into ReleaseMedia
+    ReleaseMediaFields {
+        medium, qty,
+    }
+}
+
+vn_field_tree! {
+    /// Fields of a visual novel a release belongs to, read into ReleaseVnRelation
+    ReleaseVnFields {
+        rtype,
+    }
+    nested {}
+}
+
+field_tree! {
+    /// Fields of a producer involved in a release, read into ReleaseProducer
+    ReleaseProducerFields {
+        developer, publisher, id, name, original, aliases, lang, type_ = "type", description,
+    }
+}
+
+field_tree! {
+    /// Fields of a character, see https://api.vndb.org/kana#character-fields
+    CharacterFields {
+        id, name, original, aliases, description, blood_type, height, weight, bust, waist, hips,
+        cup, age, birthday, sex, gender,
+    }
+    nested {
+        image: CharacterImageFields,
+        vns: CharacterVnFields,
+        traits: CharacterTraitFields,
+    }
+}
+
+field_tree! {
+    /// Fields of a character image, read into CharacterImage
+    CharacterImageFields {
+        id, url, dims, sexual, violence, votecount,
+    }
+}
+
+vn_field_tree! {
+    /// Fields of a visual novel a character appears in, read into CharacterVnRelation
+    CharacterVnFields {
+        spoiler, role,
+    }
+    nested {
+        release: ReleaseFields,
+    }
+}
+
+field_tree! {
+    /// Fields of a character trait, read into CharacterTrait
+    CharacterTraitFields {
+        spoiler, lie, id, name, aliases, description, searchable, applicable, group_id,
+        group_name, char_count,
+    }
+}
+
+impl QueryBuilder<VnQuery> {
+    /// Fields chosen as a tree, including the fields of nested objects at any depth
+    pub fn select(mut self, fields: VnFields) -> Self {
+        self.fields = Some(fields.to_string());
+        self
+    }
+}
+
+impl QueryBuilder<ReleaseQuery> {
+    /// Fields chosen as a tree, including the fields of nested objects at any depth
+    pub fn select(mut self, fields: ReleaseFields) -> Self {
+        self.fields = Some(fields.to_string());
+        self
+    }
+}
+
+impl QueryBuilder<CharacterQuery> {
+    /// Fields chosen as a tree, including the fields of nested objects at any depth
+    pub fn select(mut self, fields: CharacterFields) -> Self {
+        self.fields = Some(fields.to_string());
+        self
+    }
+}
+
+impl QueryBuilder<ProducerQuery> {
+    /// Fields chosen as a tree
+    pub fn select(mut self, fields: ProducerFields) -> Self {
+        self.fields = Some(fields.to_string());
+        self
+    }
+}
+
+impl QueryBuilder<StaffQuery> {
+    /// Fields chosen as a tree, including the fields of nested objects
+    pub fn select(mut self, fields: StaffFields) -> Self {
+        self.fields = Some(fields.to_string());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+
+    use super::*;
+    use crate::format::character::{
+        Character, CharacterImage, CharacterTrait, CharacterVnRelation,
+    };
+    use crate::format::producer::Producer;
+    use crate::format::release::{
+        ExtLink, Release, ReleaseLanguage, ReleaseMedia, ReleaseProducer, ReleaseVnRelation,
+    };
+    use crate::format::staff::{Staff, StaffAlias};
+    use crate::format::vn::{
+        VisualNovel, VnEdition, VnImage, VnRelation, VnScreenShot, VnStaff, VnTag, VnTitle,
+        VnVoiceActor,
+    };
+
+    /// Names of the fields a struct reads, taken from an empty object serialized back
+    fn struct_fields<T: DeserializeOwned + Serialize>() -> Vec<String> {
+        let empty: T = serde_json::from_str("{}").unwrap();
+        match serde_json::to_value(empty).unwrap() {
+            serde_json::Value::Object(fields) => fields.keys().cloned().collect(),
+            other => panic!("not an object: {}", other),
+        }
+    }
+
+    /// Fields read through #[serde(alias)], which serializing cannot show
+    const ALIASES: &[(&str, &str)] = &[("media", "medium")];
+
+    fn assert_read<T: DeserializeOwned + Serialize>(tree: &str, names: &[&str]) {
+        let fields = struct_fields::<T>();
+        for name in names {
+            let name = ALIASES
+                .iter()
+                .find(|(alias, _)| alias == name)
+                .map_or(*name, |(_, field)| *field);
+            assert!(
+                fields.iter().any(|field| field == name),
+                "{}::{} is not read into {}",
+                tree,
+                name,
+                std::any::type_name::<T>()
+            );
+        }
+    }
+
+    #[test]
+    fn every_selectable_field_is_read() {
+        macro_rules! check {
+            ($($tree:ident => $struct:ty),* $(,)?) => {
+                $(assert_read::<$struct>(stringify!($tree), $tree::NAMES);)*
+            };
+        }
+        check! {
+            VnFields => VisualNovel,
+            VnRelationFields => VnRelation,
+            VnTitleFields => VnTitle,
+            ImageFields => VnImage,
+            ScreenshotFields => VnScreenShot,
+            VnTagFields => VnTag,
+            VnEditionFields => VnEdition,
+            VnStaffFields => VnStaff,
+            VnVoiceActorFields => VnVoiceActor,
+            ExtLinkFields => ExtLink,
+            ProducerFields => Producer,
+            StaffFields => Staff,
+            StaffAliasFields => StaffAlias,
+            ReleaseFields => Release,
+            ReleaseLanguageFields => ReleaseLanguage,
+            ReleaseMediaFields => ReleaseMedia,
+            ReleaseVnFields => ReleaseVnRelation,
+            ReleaseProducerFields => ReleaseProducer,
+            CharacterFields => Character,
+            CharacterImageFields => CharacterImage,
+            CharacterVnFields => CharacterVnRelation,
+            CharacterTraitFields => CharacterTrait,
+        }
+    }
+
+    #[test]
+    fn builds_dotted_paths() {
+        let fields = VnFields::new()
+            .title()
+            .relations(|r| r.relation().title().developers(|d| d.name()))
+            .tags(|t| t.applicable().rating())
+            .title();
+        assert_eq!(
+            fields.to_string(),
+            "title,relations.relation,relations.title,relations.developers.name,\
+             tags.applicable,tags.rating"
+        );
+
+        let fields = ReleaseFields::new()
+            .producers(|p| p.original().type_())
+            .media(|m| m.medium())
+            .vns(|v| v.rtype().developers(|d| d.name()));
+        assert_eq!(
+            fields.paths(),
+            [
+                "producers.original",
+                "producers.type",
+                "media.medium",
+                "vns.rtype",
+                "vns.developers.name",
+            ]
+        );
+
+        let fields = CharacterFields::new()
+            .sex()
+            .vns(|v| v.role().developers(|d| d.original()))
+            .traits(|t| t.group_name());
+        assert_eq!(
+            fields.to_string(),
+            "sex,vns.role,vns.developers.original,traits.group_name"
+        );
+    }
+
+    #[test]
+    fn reads_the_selected_fields() {
+        let tag: VnTag = serde_json::from_str(r#"{"applicable": true}"#).unwrap();
+        assert_eq!(tag.applicable, Some(true));
+
+        let character: Character = serde_json::from_str(r#"{"sex": ["f", "m"]}"#).unwrap();
+        assert_eq!(character.sex.map(|sex| sex.len()), Some(2));
+        let character: Character = serde_json::from_str(r#"{"sex": ["f", null]}"#).unwrap();
+        assert!(matches!(character.sex.as_deref(), Some([Some(_), None])));
+
+        let producer: Producer = serde_json::from_str(r#"{"original": "株式会社"}"#).unwrap();
+        assert_eq!(producer.original.as_deref(), Some("株式会社"));
+
+        let producer: ReleaseProducer =
+            serde_json::from_str(r#"{"original": "株式会社", "type": "co"}"#).unwrap();
+        assert_eq!(producer.original.as_deref(), Some("株式会社"));
+        assert!(producer.r#type.is_some());
+    }
+}
@@ -0,0 +1,710 @@
+use serde_json::{json, Value};
+
+use crate::format::error::FilterError;
+use crate::request::filter::entity::{Entity, FilterEntity};
+use crate::request::filter::typed::{ReleaseDate, TypedFilter};
+use crate::request::filter::{Filter, Operator};
+
+/// Digits of the compact integer encoding, the first 49 encode 0 to 48 as a single character
+/// and the others prefix a base 64 number of 1 to 10 digits
+const ALPHA: &[u8; 64] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_-";
+const SINGLE_DIGITS: u64 = 49;
+const MAX_DIGITS: u32 = 10;
+
+/// Operators in the order of their compact codes
+const OPERATORS: [Operator; 6] = [
+    Operator::Eq,
+    Operator::NotEq,
+    Operator::GtEq,
+    Operator::Gt,
+    Operator::LtEq,
+    Operator::Lt,
+];
+
+/// Characters written as "_" followed by their position in this list
+const ESCAPED: &str = " !\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+const STRING_END: char = '-';
+
+/// Deepest nesting of groups and nested filters accepted by decode()
+pub const MAX_DEPTH: usize = 64;
+
+const AND: u64 = 0;
+const OR: u64 = 1;
+
+const TYPE_INT: u64 = 0;
+const TYPE_NESTED: u64 = 1;
+const TYPE_SHORT_STRING: u64 = 2;
+const TYPE_STRING: u64 = 3;
+const TYPE_ARRAY: u64 = 4;
+
+/// How the value of a filter field is encoded
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ValueKind {
+    /// VNDB id with the given prefix, only the number is encoded
+    Id(char),
+    Int,
+    Str,
+    /// Release date, encoded as a yyyymmdd number
+    Date,
+    /// Filter of another entry type
+    Nested(Entity),
+    /// Tag id or [tag id, max spoiler, min level]
+    Tag,
+    /// Trait id or [trait id, max spoiler]
+    Trait,
+    /// Two numbers such as [width, height]
+    Pair,
+    /// [user id, label id]
+    Label,
+}
+
+/// Filter field and the number standing for it in compact strings
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CompactField {
+    pub number: u64,
+    pub name: &'static str,
+    pub kind: ValueKind,
+}
+
+const fn field(number: u64, name: &'static str, kind: ValueKind) -> CompactField {
+    CompactField { number, name, kind }
+}
+
+const VN_FIELDS: &[CompactField] = &[
+    field(2, "lang", ValueKind::Str),
+    field(3, "olang", ValueKind::Str),
+    field(4, "platform", ValueKind::Str),
+    field(5, "length", ValueKind::Int),
+    field(7, "released", ValueKind::Date),
+    field(8, "tag", ValueKind::Tag),
+    field(9, "dtag", ValueKind::Tag),
+    field(10, "rating", ValueKind::Int),
+    field(11, "votecount", ValueKind::Int),
+    field(12, "label", ValueKind::Label),
+    field(13, "anime_id", ValueKind::Int),
+    field(50, "release", ValueKind::Nested(Entity::Release)),
+    field(51, "character", ValueKind::Nested(Entity::Character)),
+    field(52, "staff", ValueKind::Nested(Entity::Staff)),
+    field(55, "developer", ValueKind::Nested(Entity::Producer)),
+    field(61, "has_description", ValueKind::Int),
+    field(62, "has_anime", ValueKind::Int),
+    field(63, "has_screenshot", ValueKind::Int),
+    field(64, "has_review", ValueKind::Int),
+    field(66, "devstatus", ValueKind::Int),
+    field(80, "id", ValueKind::Id('v')),
+    field(81, "search", ValueKind::Str),
+];
+
+const RELEASE_FIELDS: &[CompactField] = &[
+    field(2, "lang", ValueKind::Str),
+    field(4, "platform", ValueKind::Str),
+    field(7, "released", ValueKind::Date),
+    field(8, "resolution", ValueKind::Pair),
+    field(9, "resolution_aspect", ValueKind::Pair),
+    field(10, "minage", ValueKind::Int),
+    field(11, "medium", ValueKind::Str),
+    field(12, "voiced", ValueKind::Int),
+    field(13, "engine", ValueKind::Str),
+    field(16, "rtype", ValueKind::Str),
+    field(19, "extlink", ValueKind::Str),
+    field(53, "vn", ValueKind::Nested(Entity::Vn)),
+    field(55, "producer", ValueKind::Nested(Entity::Producer)),
+    field(61, "patch", ValueKind::Int),
+    field(62, "freeware", ValueKind::Int),
+    field(64, "uncensored", ValueKind::Int),
+    field(65, "official", ValueKind::Int),
+    field(66, "has_ero", ValueKind::Int),
+    field(80, "id", ValueKind::Id('r')),
+    field(81, "search", ValueKind::Str),
+];
+
+const PRODUCER_FIELDS: &[CompactField] = &[
+    field(2, "lang", ValueKind::Str),
+    field(4, "type", ValueKind::Str),
+    field(80, "id", ValueKind::Id('p')),
+    field(81, "search", ValueKind::Str),
+];
+
+const CHARACTER_FIELDS: &[CompactField] = &[
+    field(2, "role", ValueKind::Str),
+    field(3, "blood_type", ValueKind::Str),
+    field(4, "sex", ValueKind::Str),
+    field(5, "height", ValueKind::Int),
+    field(6, "weight", ValueKind::Int),
+    field(7, "bust", ValueKind::Int),
+    field(8, "waist", ValueKind::Int),
+    field(9, "hips", ValueKind::Int),
+    field(10, "cup", ValueKind::Str),
+    field(11, "age", ValueKind::Int),
+    field(12, "trait", ValueKind::Trait),
+    field(13, "dtrait", ValueKind::Trait),
+    field(14, "birthday", ValueKind::Pair),
+    field(52, "seiyuu", ValueKind::Nested(Entity::Staff)),
+    field(53, "vn", ValueKind::Nested(Entity::Vn)),
+    field(80, "id", ValueKind::Id('c')),
+    field(81, "search", ValueKind::Str),
+];
+
+const STAFF_FIELDS: &[CompactField] = &[
+    field(2, "lang", ValueKind::Str),
+    field(3, "gender", ValueKind::Str),
+    field(4, "role", ValueKind::Str),
+    field(5, "extlink", ValueKind::Str),
+    field(6, "aid", ValueKind::Int),
+    field(61, "ismain", ValueKind::Int),
+    field(80, "id", ValueKind::Id('s')),
+    field(81, "search", ValueKind::Str),
+];
+
+const TAG_FIELDS: &[CompactField] = &[
+    field(2, "category", ValueKind::Str),
+    field(80, "id", ValueKind::Id('g')),
+    field(81, "search", ValueKind::Str),
+];
+
+const TRAIT_FIELDS: &[CompactField] = &[
+    field(80, "id", ValueKind::Id('i')),
+    field(81, "search", ValueKind::Str),
+];
+
+/// Fields of an entry type that can be written in compact strings
+pub fn fields(entity: Entity) -> &'static [CompactField] {
+    match entity {
+        Entity::Vn => VN_FIELDS,
+        Entity::Release => RELEASE_FIELDS,
+        Entity::Producer => PRODUCER_FIELDS,
+        Entity::Character => CHARACTER_FIELDS,
+        Entity::Staff => STAFF_FIELDS,
+        Entity::Tag => TAG_FIELDS,
+        Entity::Trait => TRAIT_FIELDS,
+    }
+}
+
+/// Writes filters of the given entry type as a compact string,
+/// the format of Response.compact_filters and of the f parameter of vndb.org URLs
+pub fn encode(entity: Entity, filter: &Filter) -> Result<String, FilterError> {
+    let mut out = String::new();
+    encode_filter(entity, filter, &mut out)?;
+    Ok(out)
+}
+
+/// Reads a compact string into filters of the given entry type
+pub fn decode(entity: Entity, compact: &str) -> Result<Filter, FilterError> {
+    let mut reader = Reader {
+        input: compact,
+        pos: 0,
+        depth: 0,
+    };
+    let filter = reader.filter(entity)?;
+    if reader.pos < compact.len() {
+        return Err(reader.error("unexpected characters after the filters"));
+    }
+    Ok(filter)
+}
+
+impl<T: FilterEntity> TypedFilter<T> {
+    /// Compact string representation, see compact::encode()
+    pub fn to_compact(&self) -> Result<String, FilterError> {
+        encode(T::ENTITY, self.filter())
+    }
+
+    /// Reads a compact string, e.g. the compact_filters of a response
+    pub fn from_compact(compact: &str) -> Result<Self, FilterError> {
+        decode(T::ENTITY, compact).map(TypedFilter::unchecked)
+    }
+}
+
+fn invalid(message: String) -> FilterError {
+    FilterError::Compact(message)
+}
+
+fn encode_filter(entity: Entity, filter: &Filter, out: &mut String) -> Result<(), FilterError> {
+    match filter {
+        Filter::And(filters) | Filter::Or(filters) => {
+            push_int(
+                if matches!(filter, Filter::And(_)) {
+                    AND
+                } else {
+                    OR
+                },
+                out,
+            );
+            push_int(filters.len() as u64, out);
+            for filter in filters {
+                encode_filter(entity, filter, out)?;
+            }
+        }
+        Filter::Predicate { field, op, value } => {
+            let compact_field =
+                fields(entity)
+                    .iter()
+                    .find(|f| f.name == field)
+                    .ok_or_else(|| {
+                        invalid(format!("{:?} has no compact code for {:?}", entity, field))
+                    })?;
+            let op_code = OPERATORS.iter().position(|o| o == op).unwrap_or(0) as u64;
+            let mut encoded = String::new();
+            let value_type = encode_value(compact_field, value, &mut encoded)?;
+            push_int(compact_field.number, out);
+            push_int(op_code + 8 * value_type, out);
+            out.push_str(&encoded);
+        }
+    }
+    Ok(())
+}
+
+fn encode_value(field: &CompactField, value: &Value, out: &mut String) -> Result<u64, FilterError> {
+    let mismatch = || invalid(format!("unexpected value {} for {:?}", value, field.name));
+    match (field.kind, value) {
+        (ValueKind::Id(prefix), value) => {
+            push_int(id_number(value, prefix).ok_or_else(mismatch)?, out);
+            Ok(TYPE_INT)
+        }
+        (ValueKind::Int, Value::Number(n)) => {
+            let n = n.as_u64().ok_or_else(|| {
+                invalid(format!(
+                    "{:?} only takes whole numbers from 0, found {}",
+                    field.name, n
+                ))
+            })?;
+            push_int(n, out);
+            Ok(TYPE_INT)
+        }
+        (ValueKind::Str, Value::String(s)) => {
+            if s.len() == 2
+                && s.bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+            {
+                out.push_str(s);
+                Ok(TYPE_SHORT_STRING)
+            } else {
+                push_string(s, out);
+                Ok(TYPE_STRING)
+            }
+        }
+        (ValueKind::Date, Value::String(s)) => {
+            let date = s.parse::<ReleaseDate>().map_err(invalid)?;
+            push_int(date_number(date), out);
+            Ok(TYPE_INT)
+        }
+        (ValueKind::Nested(entity), value) => {
+            encode_filter(entity, &Filter::from_value(value)?, out)?;
+            Ok(TYPE_NESTED)
+        }
+        (ValueKind::Tag | ValueKind::Trait, Value::String(_)) => {
+            let prefix = if field.kind == ValueKind::Tag {
+                'g'
+            } else {
+                'i'
+            };
+            push_int(id_number(value, prefix).ok_or_else(mismatch)?, out);
+            Ok(TYPE_INT)
+        }
+        (ValueKind::Tag, Value::Array(items)) => match items.as_slice() {
+            [id, spoiler, level] => {
+                push_int(id_number(id, 'g').ok_or_else(mismatch)?, out);
+                push_int(spoiler.as_u64().ok_or_else(mismatch)?, out);
+                let level = level.as_f64().filter(|l| *l >= 0.0).ok_or_else(mismatch)?;
+                push_int((level * 100.0).round() as u64, out);
+                Ok(TYPE_ARRAY)
+            }
+            _ => Err(mismatch()),
+        },
+        (ValueKind::Trait, Value::Array(items)) => match items.as_slice() {
+            [id, spoiler] => {
+                push_int(id_number(id, 'i').ok_or_else(mismatch)?, out);
+                push_int(spoiler.as_u64().ok_or_else(mismatch)?, out);
+                Ok(TYPE_ARRAY)
+            }
+            _ => Err(mismatch()),
+        },
+        (ValueKind::Pair | ValueKind::Label, Value::Array(items)) => match items.as_slice() {
+            [first, second] => {
+                let first = match field.kind {
+                    ValueKind::Label => id_number(first, 'u'),
+                    _ => first.as_u64(),
+                };
+                push_int(first.ok_or_else(mismatch)?, out);
+                push_int(second.as_u64().ok_or_else(mismatch)?, out);
+                Ok(TYPE_ARRAY)
+            }
+            _ => Err(mismatch()),
+        },
+        _ => Err(mismatch()),
+    }
+}
+
+/// Number of an id such as "v17", bare numbers are accepted as well
+fn id_number(value: &Value, prefix: char) -> Option<u64> {
+    match value {
+        Value::String(s) => s.strip_prefix(prefix).unwrap_or(s).parse().ok(),
+        Value::Number(n) => n.as_u64(),
+        _ => None,
+    }
+}
+
+/// Release dates as numbers the way VNDB stores them:
+/// 0 is unknown, 1 is today, 99999999 is TBA and 99 stands for a missing month or day
+fn date_number(date: ReleaseDate) -> u64 {
+    match date {
+        ReleaseDate::Date { year, month, day } => {
+            year as u64 * 10000 + month as u64 * 100 + day as u64
+        }
+        ReleaseDate::Month { year, month } => year as u64 * 10000 + month as u64 * 100 + 99,
+        ReleaseDate::Year(year) => year as u64 * 10000 + 9999,
+        ReleaseDate::Tba => 99999999,
+        ReleaseDate::Unknown => 0,
+        ReleaseDate::Today => 1,
+    }
+}
+
+fn number_date(n: u64) -> Option<ReleaseDate> {
+    let (year, month, day) = ((n / 10000) as u16, (n / 100 % 100) as u8, (n % 100) as u8);
+    match n {
+        0 => Some(ReleaseDate::Unknown),
+        1 => Some(ReleaseDate::Today),
+        99999999 => Some(ReleaseDate::Tba),
+        _ if month == 99 => Some(ReleaseDate::Year(year)),
+        _ if day == 99 => Some(ReleaseDate::Month { year, month }),
+        _ if (1..=12).contains(&month) && (1..=31).contains(&day) => {
+            Some(ReleaseDate::Date { year, month, day })
+        }
+        _ => None,
+    }
+}
+
+fn push_int(n: u64, out: &mut String) {
+    if n < SINGLE_DIGITS {
+        out.push(ALPHA[n as usize] as char);
+        return;
+    }
+    let mut rest = n - SINGLE_DIGITS;
+    let mut digits = 1;
+    while digits < MAX_DIGITS && rest >= 64u64.pow(digits) {
+        rest -= 64u64.pow(digits);
+        digits += 1;
+    }
+    out.push(ALPHA[(SINGLE_DIGITS + digits as u64 - 1) as usize] as char);
+    for i in (0..digits).rev() {
+        out.push(ALPHA[(rest >> (6 * i) & 63) as usize] as char);
+    }
+}
+
+fn push_string(s: &str, out: &mut String) {
+    for c in s.chars() {
+        match ESCAPED.find(c) {
+            Some(index) => {
+                out.push('_');
+                out.push(ALPHA[index] as char);
+            }
+            None => out.push(c),
+        }
+    }
+    out.push(STRING_END);
+}
+
+struct Reader<'a> {
+    input: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl Reader<'_> {
+    fn error(&self, message: &str) -> FilterError {
+        invalid(format!("{} at position {}", message, self.pos))
+    }
+
+    fn next_char(&mut self) -> Result<char, FilterError> {
+        let c = self.input[self.pos..]
+            .chars()
+            .next()
+            .ok_or_else(|| self.error("unexpected end of the string"))?;
+        self.pos += c.len_utf8();
+        Ok(c)
+    }
+
+    fn digit(&mut self) -> Result<u64, FilterError> {
+        let c = self.next_char()?;
+        ALPHA
+            .iter()
+            .position(|a| *a as char == c)
+            .map(|d| d as u64)
+            .ok_or_else(|| invalid(format!("unexpected {:?} at position {}", c, self.pos - 1)))
+    }
+
+    fn int(&mut self) -> Result<u64, FilterError> {
+        let first = self.digit()?;
+        if first < SINGLE_DIGITS {
+            return Ok(first);
+        }
+        let digits = (first - SINGLE_DIGITS + 1) as u32;
+        if digits > MAX_DIGITS {
+            return Err(self.error("number too large"));
+        }
+        let mut n = SINGLE_DIGITS + (1..digits).map(|d| 64u64.pow(d)).sum::<u64>();
+        let mut rest = 0;
+        for _ in 0..digits {
+            rest = rest << 6 | self.digit()?;
+        }
+        n += rest;
+        Ok(n)
+    }
+
+    fn string(&mut self) -> Result<String, FilterError> {
+        let mut s = String::new();
+        loop {
+            match self.next_char()? {
+                STRING_END => return Ok(s),
+                '_' => {
+                    let index = self.digit()? as usize;
+                    let c = ESCAPED
+                        .chars()
+                        .nth(index)
+                        .ok_or_else(|| self.error("unknown escape"))?;
+                    s.push(c);
+                }
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn filter(&mut self, entity: Entity) -> Result<Filter, FilterError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(&format!("filters nested deeper than {}", MAX_DEPTH)));
+        }
+        self.depth += 1;
+        let filter = self.filter_at_depth(entity);
+        self.depth -= 1;
+        filter
+    }
+
+    fn filter_at_depth(&mut self, entity: Entity) -> Result<Filter, FilterError> {
+        let start = self.pos;
+        let code = self.int()?;
+        if code == AND || code == OR {
+            let count = self.int()?;
+            let filters = (0..count)
+                .map(|_| self.filter(entity))
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(if code == AND {
+                Filter::And(filters)
+            } else {
+                Filter::Or(filters)
+            });
+        }
+
+        let field = *fields(entity)
+            .iter()
+            .find(|f| f.number == code)
+            .ok_or_else(|| {
+                invalid(format!(
+                    "unknown {:?} field {} at position {}",
+                    entity, code, start
+                ))
+            })?;
+        let op_type = self.int()?;
+        let op = *OPERATORS
+            .get((op_type % 8) as usize)
+            .ok_or_else(|| self.error("unknown operator"))?;
+        let value = self.value(&field, op_type / 8)?;
+        Ok(Filter::new(field.name, op, value))
+    }
+
+    fn value(&mut self, field: &CompactField, value_type: u64) -> Result<Value, FilterError> {
+        let mismatch =
+            |reader: &Self| reader.error(&format!("unexpected value type for {:?}", field.name));
+        match (value_type, field.kind) {
+            (TYPE_INT, ValueKind::Id(prefix)) => Ok(json!(format!("{}{}", prefix, self.int()?))),
+            (TYPE_INT, ValueKind::Tag) => Ok(json!(format!("g{}", self.int()?))),
+            (TYPE_INT, ValueKind::Trait) => Ok(json!(format!("i{}", self.int()?))),
+            (TYPE_INT, ValueKind::Int) => Ok(json!(self.int()?)),
+            (TYPE_INT, ValueKind::Date) => {
+                let date = number_date(self.int()?).ok_or_else(|| self.error("invalid date"))?;
+                Ok(json!(date.to_string()))
+            }
+            (TYPE_NESTED, ValueKind::Nested(entity)) => Ok(self.filter(entity)?.to_value()),
+            (TYPE_SHORT_STRING, ValueKind::Str) => {
+                let s = format!("{}{}", self.next_char()?, self.next_char()?);
+                Ok(json!(s))
+            }
+            (TYPE_STRING, ValueKind::Str) => Ok(json!(self.string()?)),
+            (TYPE_ARRAY, ValueKind::Tag) => {
+                let (id, spoiler, level) = (self.int()?, self.int()?, self.int()?);
+                Ok(json!([format!("g{}", id), spoiler, level as f64 / 100.0]))
+            }
+            (TYPE_ARRAY, ValueKind::Trait) => {
+                let (id, spoiler) = (self.int()?, self.int()?);
+                Ok(json!([format!("i{}", id), spoiler]))
+            }
+            (TYPE_ARRAY, ValueKind::Pair) => Ok(json!([self.int()?, self.int()?])),
+            (TYPE_ARRAY, ValueKind::Label) => {
+                let (user, label) = (self.int()?, self.int()?);
+                Ok(json!([format!("u{}", user), label]))
+            }
+            _ => Err(mismatch(self)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Compact and normalized filters returned by VNDB, written by `cargo run -- capture` in
+    /// examples/compact_filters
+    const FIXTURES: &str = include_str!("compact_fixtures.json");
+
+    #[derive(serde::Deserialize)]
+    struct Fixture {
+        entity: String,
+        compact_filters: String,
+        normalized_filters: Value,
+    }
+
+    fn fixtures() -> Vec<Fixture> {
+        serde_json::from_str(FIXTURES).expect("invalid compact_fixtures.json")
+    }
+
+    fn filter(json: &str) -> Filter {
+        Filter::from_value(&serde_json::from_str(json).unwrap()).unwrap()
+    }
+
+    fn assert_round_trip(entity: Entity, json: &str) {
+        let original = filter(json);
+        let compact = encode(entity, &original).unwrap();
+        assert_eq!(decode(entity, &compact).unwrap(), original, "{}", compact);
+    }
+
+    #[test]
+    fn integers_round_trip() {
+        for n in [0, 1, 48, 49, 112, 113, 4208, 4209, 1 << 40, u64::MAX >> 4] {
+            let mut out = String::new();
+            push_int(n, &mut out);
+            let mut reader = Reader {
+                input: &out,
+                pos: 0,
+                depth: 0,
+            };
+            assert_eq!(reader.int().unwrap(), n, "{}", out);
+            assert_eq!(reader.pos, out.len());
+        }
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let cases = [
+            (Entity::Vn, r#"["olang", "=", "ja"]"#),
+            (Entity::Vn, r#"["lang", "!=", "zh-Hans"]"#),
+            (Entity::Vn, r#"["id", ">=", "v17"]"#),
+            (Entity::Vn, r#"["search", "=", "Fate/stay night (2004)"]"#),
+            (Entity::Vn, r#"["released", ">=", "2020-01-01"]"#),
+            (Entity::Vn, r#"["released", "<", "2010"]"#),
+            (Entity::Vn, r#"["released", "<=", "2010-05"]"#),
+            (Entity::Vn, r#"["rating", ">", 80]"#),
+            (Entity::Vn, r#"["votecount", "<", 100000]"#),
+            (Entity::Vn, r#"["has_anime", "=", 1]"#),
+            (Entity::Vn, r#"["tag", "=", "g7"]"#),
+            (Entity::Vn, r#"["tag", "=", ["g7", 1, 1.5]]"#),
+            (Entity::Vn, r#"["label", "=", ["u1", 5]]"#),
+            (Entity::Vn, r#"["developer", "=", ["id", "=", "p98"]]"#),
+            (Entity::Release, r#"["platform", "=", "swi"]"#),
+            (Entity::Release, r#"["resolution", ">=", [1920, 1080]]"#),
+            (Entity::Release, r#"["minage", "<", 18]"#),
+            (Entity::Release, r#"["vn", "=", ["id", "=", "v17"]]"#),
+            (Entity::Producer, r#"["type", "=", "co"]"#),
+            (Entity::Character, r#"["trait", "=", ["i5", 0]]"#),
+            (Entity::Character, r#"["birthday", "=", [2, 14]]"#),
+            (Entity::Staff, r#"["gender", "=", "f"]"#),
+            (Entity::Tag, r#"["category", "=", "cont"]"#),
+            (Entity::Trait, r#"["search", "=", "blond"]"#),
+        ];
+        for (entity, json) in cases {
+            assert_round_trip(entity, json);
+        }
+    }
+
+    #[test]
+    fn groups_round_trip() {
+        assert_round_trip(
+            Entity::Vn,
+            r#"["and", ["or", ["lang", "=", "en"], ["lang", "=", "de"]], ["olang", "!=", "ja"],
+                ["release", "=", ["and", ["released", ">=", "2020-01-01"],
+                    ["producer", "=", ["id", "=", "p30"]]]]]"#,
+        );
+        assert_round_trip(
+            Entity::Vn,
+            r#"["character", "=", ["seiyuu", "=", ["id", "=", "s13"]]]"#,
+        );
+    }
+
+    // written by hand from the encoding above, see matches_captured_fixtures for VNDB's own strings
+    #[test]
+    fn decodes_hand_encoded_filters() {
+        assert_eq!(
+            decode(Entity::Vn, "023gjaa2Nv").unwrap(),
+            filter(r#"["and", ["olang", "=", "ja"], ["rating", ">=", 80]]"#)
+        );
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let nested = |depth| "01".repeat(depth) + "3gja";
+        assert!(decode(Entity::Vn, &nested(MAX_DEPTH - 1)).is_ok());
+        assert!(decode(Entity::Vn, &nested(MAX_DEPTH)).is_err());
+        assert!(decode(Entity::Vn, &"01".repeat(20_000)).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_strings() {
+        assert!(decode(Entity::Vn, "").is_err());
+        assert!(decode(Entity::Vn, "3gjax").is_err());
+        assert!(decode(Entity::Vn, "z0").is_err());
+        assert!(encode(Entity::Tag, &filter(r#"["olang", "=", "ja"]"#)).is_err());
+    }
+
+    #[test]
+    fn matches_captured_fixtures() {
+        for fixture in fixtures() {
+            let entity = match fixture.entity.as_str() {
+                "vn" => Entity::Vn,
+                "release" => Entity::Release,
+                "producer" => Entity::Producer,
+                "character" => Entity::Character,
+                "staff" => Entity::Staff,
+                "tag" => Entity::Tag,
+                "trait" => Entity::Trait,
+                other => panic!("unknown fixture entity {:?}", other),
+            };
+            let normalized = Filter::from_value(&fixture.normalized_filters).unwrap();
+            assert_eq!(
+                decode(entity, &fixture.compact_filters).unwrap(),
+                normalized,
+                "{}",
+                fixture.compact_filters
+            );
+            assert_eq!(
+                encode(entity, &normalized).unwrap(),
+                fixture.compact_filters,
+                "{}",
+                fixture.normalized_filters
+            );
+        }
+    }
+
+    // fails until the fixtures are captured from the live API, the field numbers are unverified
+    #[test]
+    #[ignore = "compact_fixtures.json has not been captured from api.vndb.org yet"]
+    fn fixtures_are_captured() {
+        assert!(!fixtures().is_empty());
+    }
+
+    #[test]
+    fn rejects_fractional_numbers() {
+        let error = encode(Entity::Vn, &filter(r#"["rating", ">=", 7.5]"#)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid compact filter: \"rating\" only takes whole numbers from 0, found 7.5"
+        );
+        assert!(encode(Entity::Vn, &filter(r#"["rating", ">=", -1]"#)).is_err());
+    }
+}
@@ -0,0 +1,267 @@
+use crate::format::character::{CharacterGender, CharacterRole};
+use crate::format::producer::ProducerType;
+use crate::format::release::{ReleaseType, VoicedType};
+use crate::format::schema::{Language, Medium, Platform, StaffRole};
+use crate::format::staff::StaffGender;
+use crate::format::tag::TagFlag;
+use crate::format::vn::{VnDevStatus, VnLength};
+use crate::request::filter::typed::{
+    CharacterId, EqField, FlagField, NestedField, OrdField, Pair, ProducerId, ReleaseDate,
+    ReleaseId, StaffId, TagId, TagMatch, TraitId, TraitMatch, UserLabel, VnId,
+};
+use crate::request::query::{
+    CharacterQuery, ProducerQuery, ReleaseQuery, StaffQuery, TagQuery, TraitQuery, UListQuery,
+    VnQuery,
+};
+
+/// Database entry types that can be filtered
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Entity {
+    Vn,
+    Release,
+    Producer,
+    Character,
+    Staff,
+    Tag,
+    Trait,
+}
+
+impl Entity {
+    /// Letter VNDB ids of this entry type start with, e.g. 'v' for "v17"
+    pub fn id_prefix(&self) -> char {
+        match self {
+            Entity::Vn => 'v',
+            Entity::Release => 'r',
+            Entity::Producer => 'p',
+            Entity::Character => 'c',
+            Entity::Staff => 's',
+            Entity::Tag => 'g',
+            Entity::Trait => 'i',
+        }
+    }
+}
+
+/// Query markers whose filters apply to a given entry type
+pub trait FilterEntity {
+    const ENTITY: Entity;
+}
+
+macro_rules! impl_filter_entity {
+    ($($marker:ty => $entity:ident),*) => {
+        $(impl FilterEntity for $marker {
+            const ENTITY: Entity = Entity::$entity;
+        })*
+    };
+}
+
+impl_filter_entity!(
+    VnQuery => Vn,
+    UListQuery => Vn,
+    ReleaseQuery => Release,
+    ProducerQuery => Producer,
+    CharacterQuery => Character,
+    StaffQuery => Staff,
+    TagQuery => Tag,
+    TraitQuery => Trait
+);
+
+/// Declares the filter fields of an endpoint as functions returning typed field handles
+macro_rules! filter_fields {
+    ($entity:ty; $($(#[$doc:meta])* $fn:ident: $kind:ident $(<$value:ty>)? = $name:literal;)*) => {
+        $(
+            $(#[$doc])*
+            pub fn $fn() -> $kind<$entity $(, $value)?> {
+                $kind::new($name)
+            }
+        )*
+    };
+}
+
+/// Filter fields shared by /vn and /ulist, the latter filtering the visual novels on the list
+macro_rules! vn_filter_fields {
+    ($entity:ty) => {
+        filter_fields! { $entity;
+            /// Visual novel id, e.g. "v17"
+            id: OrdField<VnId> = "id";
+            /// Matches titles and aliases
+            search: EqField<String> = "search";
+            /// Language the visual novel is available in
+            lang: EqField<Language> = "lang";
+            /// Language the visual novel was originally written in
+            olang: EqField<Language> = "olang";
+            platform: EqField<Platform> = "platform";
+            length: OrdField<VnLength> = "length";
+            released: OrdField<ReleaseDate> = "released";
+            /// Bayesian rating between 10 and 100
+            rating: OrdField<u32> = "rating";
+            votecount: OrdField<u32> = "votecount";
+            has_description: FlagField = "has_description";
+            has_anime: FlagField = "has_anime";
+            has_screenshot: FlagField = "has_screenshot";
+            has_review: FlagField = "has_review";
+            devstatus: EqField<VnDevStatus> = "devstatus";
+            /// Tag applied to the visual novel, also matches its child tags
+            tag: EqField<TagMatch> = "tag";
+            /// Tag applied directly to the visual novel, child tags are not matched
+            dtag: EqField<TagMatch> = "dtag";
+            /// AniDB anime id
+            anime_id: EqField<u32> = "anime_id";
+            /// Visual novels in a user's list with the given label
+            label: EqField<UserLabel> = "label";
+            /// Visual novels with at least one release matching a release filter
+            release: NestedField<ReleaseQuery> = "release";
+            /// Visual novels with at least one character matching a character filter
+            character: NestedField<CharacterQuery> = "character";
+            /// Visual novels with at least one staff member matching a staff filter
+            staff: NestedField<StaffQuery> = "staff";
+            /// Visual novels developed by a producer matching a producer filter
+            developer: NestedField<ProducerQuery> = "developer";
+        }
+    };
+}
+
+/// Filter fields accepted by /vn, see https://api.vndb.org/kana#post-vn
+pub struct VnFilter;
+
+impl VnFilter {
+    vn_filter_fields!(VnQuery);
+}
+
+/// Filter fields accepted by /ulist, the same fields as /vn applied to the visual novels on the list
+pub struct UListFilter;
+
+impl UListFilter {
+    vn_filter_fields!(UListQuery);
+}
+
+/// Filter fields accepted by /release, see https://api.vndb.org/kana#post-release
+pub struct ReleaseFilter;
+
+impl ReleaseFilter {
+    filter_fields! { ReleaseQuery;
+        /// Release id, e.g. "r1"
+        id: OrdField<ReleaseId> = "id";
+        search: EqField<String> = "search";
+        lang: EqField<Language> = "lang";
+        platform: EqField<Platform> = "platform";
+        released: OrdField<ReleaseDate> = "released";
+        /// Resolution as (width, height)
+        resolution: OrdField<Pair> = "resolution";
+        /// Same as resolution, but only matches releases with the same aspect ratio
+        resolution_aspect: OrdField<Pair> = "resolution_aspect";
+        /// Minimum age rating
+        minage: OrdField<u32> = "minage";
+        medium: EqField<Medium> = "medium";
+        voiced: EqField<VoicedType> = "voiced";
+        engine: EqField<String> = "engine";
+        rtype: EqField<ReleaseType> = "rtype";
+        /// URL of an external link
+        extlink: EqField<String> = "extlink";
+        patch: FlagField = "patch";
+        freeware: FlagField = "freeware";
+        uncensored: FlagField = "uncensored";
+        official: FlagField = "official";
+        has_ero: FlagField = "has_ero";
+        /// Releases of at least one visual novel matching a vn filter
+        vn: NestedField<VnQuery> = "vn";
+        /// Releases by a producer matching a producer filter
+        producer: NestedField<ProducerQuery> = "producer";
+    }
+}
+
+/// Filter fields accepted by /producer, see https://api.vndb.org/kana#post-producer
+pub struct ProducerFilter;
+
+impl ProducerFilter {
+    filter_fields! { ProducerQuery;
+        /// Producer id, e.g. "p1"
+        id: OrdField<ProducerId> = "id";
+        search: EqField<String> = "search";
+        lang: EqField<Language> = "lang";
+        /// The type filter, named type_ since type is a keyword
+        type_: EqField<ProducerType> = "type";
+    }
+}
+
+/// Filter fields accepted by /character, see https://api.vndb.org/kana#post-character
+pub struct CharacterFilter;
+
+impl CharacterFilter {
+    filter_fields! { CharacterQuery;
+        /// Character id, e.g. "c1"
+        id: OrdField<CharacterId> = "id";
+        search: EqField<String> = "search";
+        role: EqField<CharacterRole> = "role";
+        /// Blood type: "a", "b", "ab" or "o"
+        blood_type: EqField<String> = "blood_type";
+        sex: EqField<CharacterGender> = "sex";
+        /// Height in cm
+        height: OrdField<u32> = "height";
+        /// Weight in kg
+        weight: OrdField<u32> = "weight";
+        /// Bust in cm
+        bust: OrdField<u32> = "bust";
+        /// Waist in cm
+        waist: OrdField<u32> = "waist";
+        /// Hips in cm
+        hips: OrdField<u32> = "hips";
+        /// Cup size, e.g. "AAA", "AA", "A" or "B"
+        cup: OrdField<String> = "cup";
+        age: OrdField<u32> = "age";
+        /// Trait of the character, also matches its child traits
+        /// Named trait_ since trait is a keyword
+        trait_: EqField<TraitMatch> = "trait";
+        /// Trait applied directly to the character, child traits are not matched
+        dtrait: EqField<TraitMatch> = "dtrait";
+        /// Birthday as (month, day), a day of 0 matches the whole month
+        birthday: EqField<Pair> = "birthday";
+        /// Characters voiced by a staff member matching a staff filter
+        seiyuu: NestedField<StaffQuery> = "seiyuu";
+        /// Characters appearing in a visual novel matching a vn filter
+        vn: NestedField<VnQuery> = "vn";
+    }
+}
+
+/// Filter fields accepted by /staff, see https://api.vndb.org/kana#post-staff
+pub struct StaffFilter;
+
+impl StaffFilter {
+    filter_fields! { StaffQuery;
+        /// Staff id, e.g. "s1"
+        id: OrdField<StaffId> = "id";
+        /// Alias id
+        aid: EqField<u32> = "aid";
+        search: EqField<String> = "search";
+        lang: EqField<Language> = "lang";
+        gender: EqField<StaffGender> = "gender";
+        /// Role the person has in at least one visual novel
+        role: EqField<StaffRole> = "role";
+        /// URL of an external link
+        extlink: EqField<String> = "extlink";
+        /// Only the main name of each person instead of every alias
+        ismain: FlagField = "ismain";
+    }
+}
+
+/// Filter fields accepted by /tag, see https://api.vndb.org/kana#post-tag
+pub struct TagFilter;
+
+impl TagFilter {
+    filter_fields! { TagQuery;
+        /// Tag id, e.g. "g1"
+        id: OrdField<TagId> = "id";
+        search: EqField<String> = "search";
+        category: EqField<TagFlag> = "category";
+    }
+}
+
+/// Filter fields accepted by /trait, see https://api.vndb.org/kana#post-trait
+pub struct TraitFilter;
+
+impl TraitFilter {
+    filter_fields! { TraitQuery;
+        /// Trait id, e.g. "i1"
+        id: OrdField<TraitId> = "id";
+        search: EqField<String> = "search";
+    }
+}
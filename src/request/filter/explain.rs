@@ -0,0 +1,344 @@
+use serde_json::Value;
+
+use crate::format::schema::{Language, Platform};
+use crate::request::filter::entity::{Entity, FilterEntity};
+use crate::request::filter::typed::TypedFilter;
+use crate::request::filter::{Filter, Operator};
+
+/// How a predicate on a field reads in English
+#[derive(Clone, Copy)]
+enum Phrase {
+    /// Text before the value, "not" is put in front of it for !=, e.g. "originally in Japanese"
+    Prefix(&'static str),
+    /// Something compared with the value, e.g. "with a rating of at least 80"
+    Quantity(&'static str),
+    /// Verb of a date field, e.g. "released on or after 2020-01-01"
+    Date(&'static str),
+    /// Text when the flag is set and when it is not
+    Flag(&'static str, &'static str),
+    /// Related entry matching a filter of another type, e.g. "with a release available on Windows"
+    Nested(&'static str, Entity),
+}
+
+const VN_PHRASES: &[(&str, Phrase)] = &[
+    ("id", Phrase::Quantity("an id")),
+    ("search", Phrase::Prefix("matching")),
+    ("lang", Phrase::Prefix("available in")),
+    ("olang", Phrase::Prefix("originally in")),
+    ("platform", Phrase::Prefix("available on")),
+    ("length", Phrase::Quantity("a length")),
+    ("released", Phrase::Date("released")),
+    ("rating", Phrase::Quantity("a rating")),
+    ("votecount", Phrase::Quantity("a vote count")),
+    (
+        "has_description",
+        Phrase::Flag("with a description", "without a description"),
+    ),
+    (
+        "has_anime",
+        Phrase::Flag("with an anime", "without an anime"),
+    ),
+    (
+        "has_screenshot",
+        Phrase::Flag("with screenshots", "without screenshots"),
+    ),
+    (
+        "has_review",
+        Phrase::Flag("with reviews", "without reviews"),
+    ),
+    ("devstatus", Phrase::Quantity("a development status")),
+    ("tag", Phrase::Prefix("tagged")),
+    ("dtag", Phrase::Prefix("directly tagged")),
+    ("anime_id", Phrase::Quantity("an AniDB anime id")),
+    ("label", Phrase::Prefix("in a list with")),
+    ("release", Phrase::Nested("a release", Entity::Release)),
+    (
+        "character",
+        Phrase::Nested("a character", Entity::Character),
+    ),
+    ("staff", Phrase::Nested("a staff member", Entity::Staff)),
+    ("developer", Phrase::Nested("a developer", Entity::Producer)),
+];
+
+const RELEASE_PHRASES: &[(&str, Phrase)] = &[
+    ("id", Phrase::Quantity("an id")),
+    ("search", Phrase::Prefix("matching")),
+    ("lang", Phrase::Prefix("available in")),
+    ("platform", Phrase::Prefix("available on")),
+    ("released", Phrase::Date("released")),
+    ("resolution", Phrase::Quantity("a resolution")),
+    (
+        "resolution_aspect",
+        Phrase::Quantity("a resolution in the aspect ratio"),
+    ),
+    ("minage", Phrase::Quantity("an age rating")),
+    ("medium", Phrase::Prefix("on")),
+    ("voiced", Phrase::Quantity("a voiced status")),
+    ("engine", Phrase::Prefix("made with")),
+    ("rtype", Phrase::Prefix("of type")),
+    ("extlink", Phrase::Prefix("linking to")),
+    (
+        "patch",
+        Phrase::Flag("that are patches", "that are not patches"),
+    ),
+    (
+        "freeware",
+        Phrase::Flag("that are freeware", "that are not freeware"),
+    ),
+    (
+        "uncensored",
+        Phrase::Flag("that are uncensored", "that are not uncensored"),
+    ),
+    (
+        "official",
+        Phrase::Flag("that are official", "that are unofficial"),
+    ),
+    (
+        "has_ero",
+        Phrase::Flag("with erotic scenes", "without erotic scenes"),
+    ),
+    ("vn", Phrase::Nested("a visual novel", Entity::Vn)),
+    ("producer", Phrase::Nested("a producer", Entity::Producer)),
+];
+
+const PRODUCER_PHRASES: &[(&str, Phrase)] = &[
+    ("id", Phrase::Quantity("an id")),
+    ("search", Phrase::Prefix("matching")),
+    ("lang", Phrase::Prefix("working in")),
+    ("type", Phrase::Prefix("of type")),
+];
+
+const CHARACTER_PHRASES: &[(&str, Phrase)] = &[
+    ("id", Phrase::Quantity("an id")),
+    ("search", Phrase::Prefix("matching")),
+    ("role", Phrase::Prefix("with the role")),
+    ("blood_type", Phrase::Prefix("with blood type")),
+    ("sex", Phrase::Prefix("of sex")),
+    ("height", Phrase::Quantity("a height")),
+    ("weight", Phrase::Quantity("a weight")),
+    ("bust", Phrase::Quantity("a bust")),
+    ("waist", Phrase::Quantity("a waist")),
+    ("hips", Phrase::Quantity("hips")),
+    ("cup", Phrase::Quantity("a cup size")),
+    ("age", Phrase::Quantity("an age")),
+    ("trait", Phrase::Prefix("with the trait")),
+    ("dtrait", Phrase::Prefix("directly with the trait")),
+    ("birthday", Phrase::Prefix("born on")),
+    ("seiyuu", Phrase::Nested("a voice actor", Entity::Staff)),
+    ("vn", Phrase::Nested("a visual novel", Entity::Vn)),
+];
+
+const STAFF_PHRASES: &[(&str, Phrase)] = &[
+    ("id", Phrase::Quantity("an id")),
+    ("aid", Phrase::Quantity("an alias id")),
+    ("search", Phrase::Prefix("matching")),
+    ("lang", Phrase::Prefix("working in")),
+    ("gender", Phrase::Prefix("of gender")),
+    ("role", Phrase::Prefix("credited as")),
+    ("extlink", Phrase::Prefix("linking to")),
+    (
+        "ismain",
+        Phrase::Flag("listed by their main name", "listed by an alias"),
+    ),
+];
+
+const TAG_PHRASES: &[(&str, Phrase)] = &[
+    ("id", Phrase::Quantity("an id")),
+    ("search", Phrase::Prefix("matching")),
+    ("category", Phrase::Prefix("in the category")),
+];
+
+const TRAIT_PHRASES: &[(&str, Phrase)] = &[
+    ("id", Phrase::Quantity("an id")),
+    ("search", Phrase::Prefix("matching")),
+];
+
+fn phrases(entity: Entity) -> &'static [(&'static str, Phrase)] {
+    match entity {
+        Entity::Vn => VN_PHRASES,
+        Entity::Release => RELEASE_PHRASES,
+        Entity::Producer => PRODUCER_PHRASES,
+        Entity::Character => CHARACTER_PHRASES,
+        Entity::Staff => STAFF_PHRASES,
+        Entity::Tag => TAG_PHRASES,
+        Entity::Trait => TRAIT_PHRASES,
+    }
+}
+
+fn plural(entity: Entity) -> &'static str {
+    match entity {
+        Entity::Vn => "Visual novels",
+        Entity::Release => "Releases",
+        Entity::Producer => "Producers",
+        Entity::Character => "Characters",
+        Entity::Staff => "Staff",
+        Entity::Tag => "Tags",
+        Entity::Trait => "Traits",
+    }
+}
+
+/// English sentence describing the entries a filter matches, e.g.
+/// "Visual novels originally in Japanese and released on or after 2020-01-01"
+///
+/// Fields this release does not know about are written with their name and operator
+pub fn explain(entity: Entity, filter: &Filter) -> String {
+    let conditions = conditions(entity, filter);
+    if conditions.is_empty() {
+        return plural(entity).to_string();
+    }
+    format!("{} {}", plural(entity), conditions)
+}
+
+impl<T: FilterEntity> TypedFilter<T> {
+    /// English description of the filter, see explain::explain()
+    pub fn explain(&self) -> String {
+        explain(T::ENTITY, self.filter())
+    }
+}
+
+fn conditions(entity: Entity, filter: &Filter) -> String {
+    match filter {
+        Filter::And(filters) => list(entity, filters, "and"),
+        Filter::Or(filters) if filters.is_empty() => "matching nothing".to_string(),
+        Filter::Or(filters) => format!("either {}", list(entity, filters, "or")),
+        Filter::Predicate { field, op, value } => predicate(entity, field, *op, value),
+    }
+}
+
+/// "a, b and c", an and group inside an or group reads "both a and b"
+fn list(entity: Entity, filters: &[Filter], conjunction: &str) -> String {
+    let items = filters
+        .iter()
+        .map(|filter| match filter {
+            Filter::And(inner) if conjunction == "or" && inner.len() > 1 => {
+                format!("both {}", conditions(entity, filter))
+            }
+            _ => conditions(entity, filter),
+        })
+        .collect::<Vec<_>>();
+    match items.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} {} {}", rest.join(", "), conjunction, last),
+    }
+}
+
+fn predicate(entity: Entity, field: &str, op: Operator, value: &Value) -> String {
+    let phrase = phrases(entity)
+        .iter()
+        .find(|(name, _)| *name == field)
+        .map(|(_, phrase)| *phrase);
+    match (phrase, op) {
+        (Some(Phrase::Prefix(prefix)), Operator::Eq) => {
+            format!("{} {}", prefix, text(field, value))
+        }
+        (Some(Phrase::Prefix(prefix)), Operator::NotEq) => {
+            format!("not {} {}", prefix, text(field, value))
+        }
+        (Some(Phrase::Quantity(noun)), op) => {
+            let comparison = match op {
+                Operator::Eq => "of",
+                Operator::NotEq => "other than",
+                Operator::Gt => "above",
+                Operator::GtEq => "of at least",
+                Operator::Lt => "below",
+                Operator::LtEq => "of at most",
+            };
+            format!("with {} {} {}", noun, comparison, text(field, value))
+        }
+        (Some(Phrase::Date(verb)), op) => {
+            let comparison = match op {
+                Operator::Eq => "on",
+                Operator::NotEq => "not on",
+                Operator::Gt => "after",
+                Operator::GtEq => "on or after",
+                Operator::Lt => "before",
+                Operator::LtEq => "on or before",
+            };
+            format!("{} {} {}", verb, comparison, text(field, value))
+        }
+        (Some(Phrase::Flag(set, unset)), Operator::Eq | Operator::NotEq) => {
+            let is_set = (value.as_u64() == Some(1)) == (op == Operator::Eq);
+            if is_set { set } else { unset }.to_string()
+        }
+        (Some(Phrase::Nested(noun, nested)), Operator::Eq | Operator::NotEq) => {
+            let preposition = if op == Operator::Eq {
+                "with"
+            } else {
+                "without"
+            };
+            match Filter::from_value(value) {
+                Ok(filter) => format!("{} {} {}", preposition, noun, conditions(nested, &filter)),
+                Err(_) => format!("{} {} {}", preposition, noun, value),
+            }
+        }
+        _ => format!("with {} {} {}", field, op, text(field, value)),
+    }
+}
+
+/// Value as written in a sentence, language codes are replaced by their names
+fn text(field: &str, value: &Value) -> String {
+    match (field, value) {
+        ("lang" | "olang", _) => serde_json::from_value::<Language>(value.clone())
+            .map(|language| language.to_string())
+            .unwrap_or_else(|_| plain(value)),
+        ("platform", _) => serde_json::from_value::<Platform>(value.clone())
+            .map(|platform| platform.to_string())
+            .unwrap_or_else(|_| plain(value)),
+        ("search", Value::String(s)) => format!("{:?}", s),
+        ("birthday", Value::Array(pair)) => match pair.as_slice() {
+            [month, Value::Number(day)] if day.as_u64() == Some(0) => format!("month {}", month),
+            [month, day] => format!("{}/{}", month, day),
+            _ => plain(value),
+        },
+        ("label", Value::Array(pair)) => match pair.as_slice() {
+            [user, label] => format!("label {} of user {}", label, plain(user)),
+            _ => plain(value),
+        },
+        (_, Value::Array(items)) => match items.as_slice() {
+            // tags are [id, max spoiler, min level] and traits [id, max spoiler]
+            [Value::String(id), spoiler, level] => {
+                format!(
+                    "{} (spoiler level up to {}, tag level {} or more)",
+                    id, spoiler, level
+                )
+            }
+            [Value::String(id), spoiler] => format!("{} (spoiler level up to {})", id, spoiler),
+            _ => items.iter().map(plain).collect::<Vec<_>>().join("x"),
+        },
+        _ => plain(value),
+    }
+}
+
+fn plain(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn explain_json(entity: Entity, json: &str) -> String {
+        explain(
+            entity,
+            &Filter::from_value(&serde_json::from_str(json).unwrap()).unwrap(),
+        )
+    }
+
+    #[test]
+    fn names_languages_and_platforms() {
+        let sentence = explain_json(
+            Entity::Release,
+            r#"["and", ["lang", "=", "zh-Hans"], ["platform", "=", "swi"],
+                ["platform", "=", "mob"], ["lang", "=", "xx"]]"#,
+        );
+        assert!(sentence.contains("Chinese (simplified)"), "{}", sentence);
+        assert!(sentence.contains("Nintendo Switch"), "{}", sentence);
+        assert!(sentence.contains("Other (mobile)"), "{}", sentence);
+        assert!(sentence.contains(" xx"), "{}", sentence);
+        assert!(!sentence.contains("Unknown"), "{}", sentence);
+    }
+}
@@ -0,0 +1,263 @@
+pub mod compact;
+pub mod entity;
+pub mod explain;
+pub mod normalize;
+pub mod text;
+pub mod typed;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+use std::ops::{BitAnd, BitOr};
+
+use crate::format::error::FilterError;
+
+pub use entity::{
+    CharacterFilter, Entity, FilterEntity, ProducerFilter, ReleaseFilter, StaffFilter, TagFilter,
+    TraitFilter, UListFilter, VnFilter,
+};
+pub use typed::TypedFilter;
+
+/// Comparison operators accepted in filter predicates
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Operator {
+    Eq,
+    NotEq,
+    Gt,
+    GtEq,
+    Lt,
+    LtEq,
+}
+
+impl Operator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operator::Eq => "=",
+            Operator::NotEq => "!=",
+            Operator::Gt => ">",
+            Operator::GtEq => ">=",
+            Operator::Lt => "<",
+            Operator::LtEq => "<=",
+        }
+    }
+
+    pub fn parse(op: &str) -> Option<Self> {
+        match op {
+            "=" => Some(Operator::Eq),
+            "!=" => Some(Operator::NotEq),
+            ">" => Some(Operator::Gt),
+            ">=" => Some(Operator::GtEq),
+            "<" => Some(Operator::Lt),
+            "<=" => Some(Operator::LtEq),
+            _ => None,
+        }
+    }
+
+    /// Operator matching exactly the entries this one does not
+    pub fn negate(&self) -> Self {
+        match self {
+            Operator::Eq => Operator::NotEq,
+            Operator::NotEq => Operator::Eq,
+            Operator::Gt => Operator::LtEq,
+            Operator::GtEq => Operator::Lt,
+            Operator::Lt => Operator::GtEq,
+            Operator::LtEq => Operator::Gt,
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Filters of a query, see https://api.vndb.org/kana#filters
+///
+/// Serializes to the JSON array VNDB expects:
+/// Filter::eq("olang", "ja") & Filter::ge("released", "2020-01-01")
+/// becomes ["and", ["olang", "=", "ja"], ["released", ">=", "2020-01-01"]]
+#[derive(Clone, PartialEq, Debug)]
+pub enum Filter {
+    /// Matches entries matching every filter
+    And(Vec<Filter>),
+    /// Matches entries matching at least one filter
+    Or(Vec<Filter>),
+    /// Compares a filter field against a value
+    Predicate {
+        field: String,
+        op: Operator,
+        value: Value,
+    },
+}
+
+impl Filter {
+    pub fn new(field: &str, op: Operator, value: impl Into<Value>) -> Self {
+        Filter::Predicate {
+            field: field.to_string(),
+            op,
+            value: value.into(),
+        }
+    }
+
+    pub fn eq(field: &str, value: impl Into<Value>) -> Self {
+        Self::new(field, Operator::Eq, value)
+    }
+
+    pub fn ne(field: &str, value: impl Into<Value>) -> Self {
+        Self::new(field, Operator::NotEq, value)
+    }
+
+    pub fn gt(field: &str, value: impl Into<Value>) -> Self {
+        Self::new(field, Operator::Gt, value)
+    }
+
+    pub fn ge(field: &str, value: impl Into<Value>) -> Self {
+        Self::new(field, Operator::GtEq, value)
+    }
+
+    pub fn lt(field: &str, value: impl Into<Value>) -> Self {
+        Self::new(field, Operator::Lt, value)
+    }
+
+    pub fn le(field: &str, value: impl Into<Value>) -> Self {
+        Self::new(field, Operator::LtEq, value)
+    }
+
+    pub fn and(filters: impl IntoIterator<Item = Filter>) -> Self {
+        Filter::And(filters.into_iter().collect())
+    }
+
+    pub fn or(filters: impl IntoIterator<Item = Filter>) -> Self {
+        Filter::Or(filters.into_iter().collect())
+    }
+
+    /// Filter matching exactly the entries this one does not, and/or are swapped by De Morgan's laws
+    pub fn negate(&self) -> Self {
+        match self {
+            Filter::And(filters) => Filter::Or(filters.iter().map(Filter::negate).collect()),
+            Filter::Or(filters) => Filter::And(filters.iter().map(Filter::negate).collect()),
+            Filter::Predicate { field, op, value } => Filter::Predicate {
+                field: field.clone(),
+                op: op.negate(),
+                value: value.clone(),
+            },
+        }
+    }
+
+    /// JSON representation sent to VNDB
+    pub fn to_value(&self) -> Value {
+        match self {
+            Filter::And(filters) | Filter::Or(filters) => {
+                let name = match self {
+                    Filter::And(_) => "and",
+                    _ => "or",
+                };
+                let mut array = vec![Value::from(name)];
+                array.extend(filters.iter().map(Filter::to_value));
+                Value::Array(array)
+            }
+            Filter::Predicate { field, op, value } => Value::Array(vec![
+                field.as_str().into(),
+                op.as_str().into(),
+                value.clone(),
+            ]),
+        }
+    }
+
+    /// Reads the JSON representation, e.g. the normalized_filters of a response
+    pub fn from_value(value: &Value) -> Result<Self, FilterError> {
+        let array = match value {
+            Value::Array(array) => array,
+            other => {
+                return Err(FilterError::Malformed(format!(
+                    "expected an array, found {}",
+                    other
+                )))
+            }
+        };
+        match array.first().and_then(Value::as_str) {
+            Some(combinator @ ("and" | "or")) => {
+                let filters = array[1..]
+                    .iter()
+                    .map(Filter::from_value)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(match combinator {
+                    "and" => Filter::And(filters),
+                    _ => Filter::Or(filters),
+                })
+            }
+            Some(field) => match array.as_slice() {
+                [_, Value::String(op), value] => match Operator::parse(op) {
+                    Some(op) => Ok(Filter::new(field, op, value.clone())),
+                    None => Err(FilterError::Malformed(format!("unknown operator {:?}", op))),
+                },
+                _ => Err(FilterError::Malformed(format!(
+                    "expected [field, operator, value], found {}",
+                    value
+                ))),
+            },
+            None => Err(FilterError::Malformed(format!(
+                "expected a field name or and/or, found {}",
+                value
+            ))),
+        }
+    }
+}
+
+impl BitAnd for Filter {
+    type Output = Filter;
+
+    /// Joins two filters, extending an existing and instead of nesting it
+    fn bitand(self, rhs: Filter) -> Filter {
+        match self {
+            Filter::And(mut filters) => {
+                filters.push(rhs);
+                Filter::And(filters)
+            }
+            lhs => Filter::And(vec![lhs, rhs]),
+        }
+    }
+}
+
+impl BitOr for Filter {
+    type Output = Filter;
+
+    /// Joins two filters, extending an existing or instead of nesting it
+    fn bitor(self, rhs: Filter) -> Filter {
+        match self {
+            Filter::Or(mut filters) => {
+                filters.push(rhs);
+                Filter::Or(filters)
+            }
+            lhs => Filter::Or(vec![lhs, rhs]),
+        }
+    }
+}
+
+impl From<Filter> for Value {
+    fn from(filter: Filter) -> Self {
+        filter.to_value()
+    }
+}
+
+impl TryFrom<Value> for Filter {
+    type Error = FilterError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        Filter::from_value(&value)
+    }
+}
+
+impl Serialize for Filter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_value().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Filter {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        Filter::from_value(&value).map_err(serde::de::Error::custom)
+    }
+}
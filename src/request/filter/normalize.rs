@@ -0,0 +1,205 @@
+use serde_json::Value;
+use std::cmp::Ordering;
+
+use crate::request::filter::compact::{fields, ValueKind};
+use crate::request::filter::entity::{Entity, FilterEntity};
+use crate::request::filter::typed::TypedFilter;
+use crate::request::filter::{Filter, Operator};
+
+/// Operators in the order predicates on the same field are sorted by
+const OPERATOR_ORDER: [Operator; 6] = [
+    Operator::Eq,
+    Operator::NotEq,
+    Operator::Gt,
+    Operator::GtEq,
+    Operator::Lt,
+    Operator::LtEq,
+];
+
+impl Filter {
+    /// Equivalent filter in a canonical form, two filters matching the same way compare equal
+    /// once normalized
+    ///
+    /// Nested and/or groups are flattened into their parent, groups of a single filter are
+    /// replaced by it, duplicates are removed and predicates are sorted by field, operator and
+    /// value before groups, the filters of nested fields of the entry type are normalized as well
+    ///
+    /// An empty and group matches everything and an empty or group nothing, so inside another
+    /// group they are either dropped or replace the whole group
+    pub fn normalize(&self, entity: Entity) -> Filter {
+        match self {
+            Filter::And(filters) => group(entity, filters, true),
+            Filter::Or(filters) => group(entity, filters, false),
+            Filter::Predicate { field, op, value } => Filter::Predicate {
+                field: field.clone(),
+                op: *op,
+                value: normalize_value(entity, field, value),
+            },
+        }
+    }
+}
+
+impl<T: FilterEntity> TypedFilter<T> {
+    /// Canonical form of the filter, see Filter::normalize()
+    pub fn normalize(&self) -> Self {
+        TypedFilter::unchecked(self.filter().normalize(T::ENTITY))
+    }
+}
+
+fn group(entity: Entity, filters: &[Filter], and: bool) -> Filter {
+    let mut flat = Vec::new();
+    for filter in filters.iter().map(|filter| filter.normalize(entity)) {
+        match filter {
+            Filter::And(inner) if and => flat.extend(inner),
+            Filter::Or(inner) if !and => flat.extend(inner),
+            // an empty or inside an and, or an empty and inside an or, decides the whole group
+            Filter::Or(inner) | Filter::And(inner) if inner.is_empty() => {
+                return if and {
+                    Filter::Or(Vec::new())
+                } else {
+                    Filter::And(Vec::new())
+                };
+            }
+            other => flat.push(other),
+        }
+    }
+    flat.sort_by(compare);
+    flat.dedup();
+
+    if flat.len() == 1 {
+        return flat.remove(0);
+    }
+    if and {
+        Filter::And(flat)
+    } else {
+        Filter::Or(flat)
+    }
+}
+
+/// The value of a nested field such as release or developer is a filter itself
+fn normalize_value(entity: Entity, field: &str, value: &Value) -> Value {
+    let nested = fields(entity)
+        .iter()
+        .find(|f| f.name == field)
+        .and_then(|f| match f.kind {
+            ValueKind::Nested(nested) => Some(nested),
+            _ => None,
+        });
+    match nested.map(|nested| (nested, Filter::from_value(value))) {
+        Some((nested, Ok(filter))) => filter.normalize(nested).to_value(),
+        _ => value.clone(),
+    }
+}
+
+fn compare(a: &Filter, b: &Filter) -> Ordering {
+    match (a, b) {
+        (
+            Filter::Predicate {
+                field: a_field,
+                op: a_op,
+                value: a_value,
+            },
+            Filter::Predicate {
+                field: b_field,
+                op: b_op,
+                value: b_value,
+            },
+        ) => a_field
+            .cmp(b_field)
+            .then_with(|| operator_rank(*a_op).cmp(&operator_rank(*b_op)))
+            .then_with(|| a_value.to_string().cmp(&b_value.to_string())),
+        _ => group_rank(a)
+            .cmp(&group_rank(b))
+            .then_with(|| a.to_value().to_string().cmp(&b.to_value().to_string())),
+    }
+}
+
+fn operator_rank(op: Operator) -> usize {
+    OPERATOR_ORDER.iter().position(|o| *o == op).unwrap_or(0)
+}
+
+fn group_rank(filter: &Filter) -> u8 {
+    match filter {
+        Filter::Predicate { .. } => 0,
+        Filter::And(_) => 1,
+        Filter::Or(_) => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(json: &str) -> Filter {
+        Filter::from_value(&serde_json::from_str(json).unwrap()).unwrap()
+    }
+
+    fn normalized(entity: Entity, json: &str) -> Value {
+        filter(json).normalize(entity).to_value()
+    }
+
+    #[test]
+    fn folds_empty_groups() {
+        let olang = r#"["olang", "=", "ja"]"#;
+        assert_eq!(
+            normalized(Entity::Vn, &format!(r#"["or", ["and"], {}]"#, olang)),
+            serde_json::json!(["and"])
+        );
+        assert_eq!(
+            normalized(Entity::Vn, &format!(r#"["and", ["or"], {}]"#, olang)),
+            serde_json::json!(["or"])
+        );
+        assert_eq!(
+            normalized(Entity::Vn, &format!(r#"["and", ["and"], {}]"#, olang)),
+            serde_json::json!(["olang", "=", "ja"])
+        );
+        assert_eq!(
+            normalized(Entity::Vn, &format!(r#"["or", ["or"], {}]"#, olang)),
+            serde_json::json!(["olang", "=", "ja"])
+        );
+    }
+
+    #[test]
+    fn flattens_sorts_and_dedups() {
+        assert_eq!(
+            normalized(
+                Entity::Vn,
+                r#"["and", ["rating", ">=", 80], ["and", ["olang", "=", "ja"], ["rating", ">=", 80]]]"#
+            ),
+            serde_json::json!(["and", ["olang", "=", "ja"], ["rating", ">=", 80]])
+        );
+    }
+
+    #[test]
+    fn normalizes_nested_fields_only() {
+        assert_eq!(
+            normalized(
+                Entity::Vn,
+                r#"["release", "=", ["and", ["platform", "=", "win"], ["and", ["lang", "=", "en"]]]]"#
+            ),
+            serde_json::json!([
+                "release",
+                "=",
+                ["and", ["lang", "=", "en"], ["platform", "=", "win"]]
+            ])
+        );
+        // a tag value is an array but not a filter
+        assert_eq!(
+            normalized(Entity::Vn, r#"["tag", "=", ["g505", 2, 1.2]]"#),
+            serde_json::json!(["tag", "=", ["g505", 2, 1.2]])
+        );
+    }
+
+    #[test]
+    fn normalizing_twice_changes_nothing() {
+        for json in [
+            r#"["or", ["and"], ["olang", "=", "ja"]]"#,
+            r#"["and", ["or", ["rating", ">", 70], ["or", ["votecount", ">", 50]]], ["olang", "!=", "en"]]"#,
+            r#"["and", ["release", "=", ["or", ["lang", "=", "en"], ["or", ["lang", "=", "de"]]]], ["and", ["olang", "=", "ja"]]]"#,
+            r#"["or", ["and", ["olang", "=", "ja"], ["rating", ">=", 80]], ["and", ["rating", ">=", 80], ["olang", "=", "ja"]]]"#,
+        ] {
+            let once = filter(json).normalize(Entity::Vn);
+            assert_eq!(once.normalize(Entity::Vn), once, "{}", json);
+        }
+    }
+}
@@ -0,0 +1,479 @@
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+use crate::format::character::{CharacterGender, CharacterRole};
+use crate::format::error::FilterError;
+use crate::format::producer::ProducerType;
+use crate::format::release::ReleaseType;
+use crate::format::schema::{Language, Medium, Platform, StaffRole};
+use crate::format::staff::StaffGender;
+use crate::format::tag::TagFlag;
+use crate::request::filter::compact::{fields, ValueKind, MAX_DEPTH};
+use crate::request::filter::entity::{Entity, FilterEntity};
+use crate::request::filter::typed::{ReleaseDate, TypedFilter};
+use crate::request::filter::{Filter, Operator};
+
+/// Reads a filter written in the text query language, e.g.
+/// `olang:ja released>=2020-01-01 (platform:win | platform:swi) tag:g7 -tag:g134`
+///
+/// - `field:value` or `field=value` matches a value, `!=`, `>`, `>=`, `<` and `<=` compare it
+/// - terms separated by spaces or `&` must all match, `|` matches either side
+/// - `-` in front of a term or a group matches the opposite, parentheses group terms
+/// - values with spaces, parentheses, `|` or `&` are quoted, e.g. `search:"fate stay night"`
+/// - codes such as languages and platforms must be ones this release knows, e.g. `lang:zh-Hans`
+/// - nested filters take a term or a group, e.g. `developer:(id:p98 | id:p30)`
+/// - tags and traits take an optional spoiler level and tags a minimum level, e.g. `tag:g7,1,1.5`
+/// - pairs such as resolutions and birthdays are written `1920,1080`, labels `u1,5`
+///
+/// Only the fields of the given entry type are accepted
+pub fn parse(entity: Entity, query: &str) -> Result<Filter, FilterError> {
+    let mut parser = Parser {
+        input: query,
+        pos: 0,
+        depth: 0,
+    };
+    parser.skip_whitespace();
+    if parser.peek().is_none() {
+        return Err(parser.error("empty query"));
+    }
+    let filter = parser.or(entity)?;
+    match parser.peek() {
+        None => Ok(filter),
+        Some(')') => Err(parser.error("unmatched ')'")),
+        Some(c) => Err(parser.error(&format!("unexpected {:?}", c))),
+    }
+}
+
+impl<T: FilterEntity> FromStr for TypedFilter<T> {
+    type Err = FilterError;
+
+    /// Reads a filter in the text query language, see text::parse()
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        parse(T::ENTITY, query).map(TypedFilter::unchecked)
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> FilterError {
+        self.error_at(self.pos, message)
+    }
+
+    fn error_at(&self, pos: usize, message: &str) -> FilterError {
+        FilterError::Syntax {
+            position: self.input[..pos].chars().count(),
+            message: message.to_string(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) {
+        if let Some(c) = self.peek() {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.bump();
+            return true;
+        }
+        false
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn or(&mut self, entity: Entity) -> Result<Filter, FilterError> {
+        let mut filters = vec![self.and(entity)?];
+        while self.eat('|') {
+            filters.push(self.and(entity)?);
+        }
+        Ok(combine(filters, Filter::Or))
+    }
+
+    fn and(&mut self, entity: Entity) -> Result<Filter, FilterError> {
+        let mut filters = vec![self.unary(entity)?];
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None | Some('|') | Some(')') => break,
+                Some('&') => self.bump(),
+                _ => {}
+            }
+            filters.push(self.unary(entity)?);
+        }
+        Ok(combine(filters, Filter::And))
+    }
+
+    // every negation, group and nested filter goes through here, which bounds the recursion
+    fn unary(&mut self, entity: Entity) -> Result<Filter, FilterError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(&format!("filters nested deeper than {}", MAX_DEPTH)));
+        }
+        self.depth += 1;
+        let filter = self.unary_at_depth(entity);
+        self.depth -= 1;
+        filter
+    }
+
+    fn unary_at_depth(&mut self, entity: Entity) -> Result<Filter, FilterError> {
+        self.skip_whitespace();
+        if self.eat('-') {
+            return Ok(self.unary(entity)?.negate());
+        }
+        if self.peek() == Some('(') {
+            let start = self.pos;
+            self.bump();
+            let filter = self.or(entity)?;
+            self.skip_whitespace();
+            if !self.eat(')') {
+                return Err(self.error_at(start, "unclosed '('"));
+            }
+            return Ok(filter);
+        }
+        self.predicate(entity)
+    }
+
+    fn predicate(&mut self, entity: Entity) -> Result<Filter, FilterError> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            self.bump();
+        }
+        let name = &self.input[start..self.pos];
+        if name.is_empty() {
+            return Err(match self.peek() {
+                Some(c) => self.error(&format!(
+                    "expected a filter such as olang:ja, found {:?}",
+                    c
+                )),
+                None => self.error("expected a filter such as olang:ja"),
+            });
+        }
+        let field = *fields(entity)
+            .iter()
+            .find(|field| field.name == name)
+            .ok_or_else(|| {
+                self.error_at(start, &format!("unknown {:?} filter {:?}", entity, name))
+            })?;
+
+        let op = self.operator().ok_or_else(|| {
+            self.error(&format!(
+                "expected an operator such as ':' after {:?}",
+                name
+            ))
+        })?;
+
+        let value_start = self.pos;
+        let value = match field.kind {
+            ValueKind::Nested(nested) => self.unary(nested)?.to_value(),
+            kind => {
+                let raw = self.word()?;
+                if raw.is_empty() {
+                    return Err(self.error(&format!("expected a value for {:?}", name)));
+                }
+                if known_code(entity, field.name, &raw) == Some(false) {
+                    return Err(
+                        self.error_at(value_start, &format!("unknown {:?} value {:?}", name, raw))
+                    );
+                }
+                convert(kind, &raw).map_err(|message| self.error_at(value_start, &message))?
+            }
+        };
+        Ok(Filter::new(field.name, op, value))
+    }
+
+    fn operator(&mut self) -> Option<Operator> {
+        let rest = &self.input[self.pos..];
+        let (op, len) = if rest.starts_with("!=") {
+            (Operator::NotEq, 2)
+        } else if rest.starts_with(">=") {
+            (Operator::GtEq, 2)
+        } else if rest.starts_with("<=") {
+            (Operator::LtEq, 2)
+        } else if rest.starts_with(':') || rest.starts_with('=') {
+            (Operator::Eq, 1)
+        } else if rest.starts_with('>') {
+            (Operator::Gt, 1)
+        } else if rest.starts_with('<') {
+            (Operator::Lt, 1)
+        } else {
+            return None;
+        };
+        self.pos += len;
+        Some(op)
+    }
+
+    /// A quoted string or the characters up to the next space, parenthesis, '|' or '&'
+    fn word(&mut self) -> Result<String, FilterError> {
+        let start = self.pos;
+        if !self.eat('"') {
+            while self
+                .peek()
+                .is_some_and(|c| !c.is_whitespace() && !matches!(c, '(' | ')' | '|' | '&'))
+            {
+                self.bump();
+            }
+            return Ok(self.input[start..self.pos].to_string());
+        }
+
+        let mut word = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error_at(start, "unclosed '\"'")),
+                Some('"') => {
+                    self.bump();
+                    return Ok(word);
+                }
+                Some('\\') => {
+                    self.bump();
+                    match self.peek() {
+                        Some(c) => word.push(c),
+                        None => return Err(self.error_at(start, "unclosed '\"'")),
+                    }
+                    self.bump();
+                }
+                Some(c) => {
+                    word.push(c);
+                    self.bump();
+                }
+            }
+        }
+    }
+}
+
+fn combine(mut filters: Vec<Filter>, combinator: fn(Vec<Filter>) -> Filter) -> Filter {
+    if filters.len() == 1 {
+        return filters.remove(0);
+    }
+    combinator(filters)
+}
+
+/// Whether the value of a field taking codes, such as a language or a platform, is one of them,
+/// None for other fields
+fn known_code(entity: Entity, field: &str, raw: &str) -> Option<bool> {
+    fn known<T: DeserializeOwned>(raw: &str, unknown: fn(&T) -> bool) -> bool {
+        serde_json::from_value::<T>(Value::from(raw)).is_ok_and(|value| !unknown(&value))
+    }
+    Some(match (entity, field) {
+        (_, "lang" | "olang") => known(raw, |v| matches!(v, Language::Unknown(_))),
+        (_, "platform") => known(raw, |v| matches!(v, Platform::Unknown(_))),
+        (Entity::Release, "medium") => known(raw, |v| matches!(v, Medium::Unknown(_))),
+        (Entity::Release, "rtype") => known(raw, |v| matches!(v, ReleaseType::Unknown(_))),
+        (Entity::Producer, "type") => known(raw, |v| matches!(v, ProducerType::Unknown(_))),
+        (Entity::Character, "role") => known(raw, |v| matches!(v, CharacterRole::Unknown(_))),
+        (Entity::Character, "sex") => known(raw, |v| matches!(v, CharacterGender::Unknown(_))),
+        (Entity::Staff, "gender") => known(raw, |v| matches!(v, StaffGender::Unknown(_))),
+        (Entity::Staff, "role") => known(raw, |v| matches!(v, StaffRole::Unknown(_))),
+        (Entity::Tag, "category") => known(raw, |v| matches!(v, TagFlag::Unknown(_))),
+        _ => return None,
+    })
+}
+
+/// JSON value of a field from the text written after its operator
+fn convert(kind: ValueKind, raw: &str) -> Result<Value, String> {
+    let parts = raw.split(',').collect::<Vec<_>>();
+    match kind {
+        ValueKind::Id(prefix) => id(raw, prefix).map(Value::from),
+        ValueKind::Int => number::<u64>(raw).map(Value::from),
+        ValueKind::Str => Ok(Value::from(raw)),
+        ValueKind::Date => Ok(Value::from(ReleaseDate::from_str(raw)?.to_string())),
+        ValueKind::Tag => match parts.as_slice() {
+            [tag] => id(tag, 'g').map(Value::from),
+            [tag, spoiler] => Ok(json!([id(tag, 'g')?, number::<u8>(spoiler)?, 0.0])),
+            [tag, spoiler, level] => Ok(json!([
+                id(tag, 'g')?,
+                number::<u8>(spoiler)?,
+                number::<f32>(level)?
+            ])),
+            _ => Err(format!(
+                "expected a tag such as g7 or g7,1,1.5, found {:?}",
+                raw
+            )),
+        },
+        ValueKind::Trait => match parts.as_slice() {
+            [trait_] => id(trait_, 'i').map(Value::from),
+            [trait_, spoiler] => Ok(json!([id(trait_, 'i')?, number::<u8>(spoiler)?])),
+            _ => Err(format!(
+                "expected a trait such as i5 or i5,1, found {:?}",
+                raw
+            )),
+        },
+        ValueKind::Pair => match parts.as_slice() {
+            [first, second] => Ok(json!([number::<u32>(first)?, number::<u32>(second)?])),
+            _ => Err(format!(
+                "expected two numbers such as 1920,1080, found {:?}",
+                raw
+            )),
+        },
+        ValueKind::Label => match parts.as_slice() {
+            [user, label] => Ok(json!([id(user, 'u')?, number::<u32>(label)?])),
+            _ => Err(format!(
+                "expected a user and a label such as u1,5, found {:?}",
+                raw
+            )),
+        },
+        ValueKind::Nested(_) => unreachable!("nested filters are parsed as filters"),
+    }
+}
+
+/// Id with its prefix, which can be left out, e.g. "v17" or "17" for a visual novel
+fn id(raw: &str, prefix: char) -> Result<String, String> {
+    let digits = raw.strip_prefix(prefix).unwrap_or(raw);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!(
+            "expected an id such as {}17, found {:?}",
+            prefix, raw
+        ));
+    }
+    Ok(format!("{}{}", prefix, digits))
+}
+
+fn number<N: FromStr>(raw: &str) -> Result<N, String> {
+    raw.parse()
+        .map_err(|_| format!("expected a number, found {:?}", raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(query: &str) -> Value {
+        parse(Entity::Vn, query).unwrap().to_value()
+    }
+
+    fn error_at(query: &str) -> (usize, String) {
+        match parse(Entity::Vn, query) {
+            Err(FilterError::Syntax { position, message }) => (position, message),
+            other => panic!("expected a syntax error for {:?}, got {:?}", query, other),
+        }
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let (ja, en, win) = (
+            json!(["olang", "=", "ja"]),
+            json!(["olang", "=", "en"]),
+            json!(["platform", "=", "win"]),
+        );
+        assert_eq!(
+            parsed("olang:ja platform:win | olang:en"),
+            json!(["or", ["and", ja, win], en])
+        );
+        assert_eq!(
+            parsed("olang:en | olang:ja & platform:win"),
+            json!(["or", en, ["and", ja, win]])
+        );
+        assert_eq!(
+            parsed("(olang:en | olang:ja) platform:win"),
+            json!(["and", ["or", en, ja], win])
+        );
+        assert_eq!(
+            parsed("-olang:ja | olang:en"),
+            json!(["or", ["olang", "!=", "ja"], en])
+        );
+        assert_eq!(
+            parsed("-(olang:ja | olang:en)"),
+            parse(Entity::Vn, "olang:ja | olang:en")
+                .unwrap()
+                .negate()
+                .to_value()
+        );
+    }
+
+    #[test]
+    fn ends_words_at_ampersands() {
+        assert_eq!(
+            parsed("olang:ja&platform:win"),
+            json!(["and", ["olang", "=", "ja"], ["platform", "=", "win"]])
+        );
+        assert_eq!(
+            parsed(r#"search:"this & that""#),
+            json!(["search", "=", "this & that"])
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_codes() {
+        assert_eq!(
+            error_at("olang:ja lang:xx"),
+            (14, "unknown \"lang\" value \"xx\"".to_string())
+        );
+        assert_eq!(error_at("platform:windows").0, 9);
+        assert!(parse(Entity::Release, "medium:cd rtype:complete").is_ok());
+        assert!(parse(Entity::Release, "rtype:demo").is_err());
+        assert!(parse(Entity::Staff, "gender:f role:scenario").is_ok());
+        assert!(parse(Entity::Character, "sex:x").is_err());
+        assert_eq!(parsed("lang:zh-Hans"), json!(["lang", "=", "zh-Hans"]));
+        // free text is not checked
+        assert!(parse(Entity::Release, "engine:\"Ren'Py\"").is_ok());
+    }
+
+    #[test]
+    fn nested_fields_take_one_term_or_a_group() {
+        assert_eq!(
+            parsed("release:platform:win lang:en"),
+            json!([
+                "and",
+                ["release", "=", ["platform", "=", "win"]],
+                ["lang", "=", "en"]
+            ])
+        );
+        assert_eq!(
+            parsed("release:(platform:win lang:en)"),
+            json!([
+                "release",
+                "=",
+                ["and", ["platform", "=", "win"], ["lang", "=", "en"]]
+            ])
+        );
+    }
+
+    #[test]
+    fn reports_error_positions() {
+        assert_eq!(
+            error_at("olang:ja bogus:1"),
+            (9, "unknown Vn filter \"bogus\"".to_string())
+        );
+        assert_eq!(error_at("olang:ja rating>=high").0, 17);
+        assert_eq!(error_at("olang:ja (rating>70").0, 9);
+        assert_eq!(error_at("olang:ja)").0, 8);
+        assert_eq!(error_at("search:\"fate stay").0, 7);
+        assert_eq!(error_at("olang ja").0, 5);
+        assert_eq!(error_at("id:v1 |").0, 7);
+        // positions count characters, not bytes
+        assert_eq!(error_at("search:\"ドキドキ\" rating>x").0, 21);
+        assert_eq!(error_at("").1, "empty query");
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let groups = "(".repeat(MAX_DEPTH - 1) + "olang:ja" + &")".repeat(MAX_DEPTH - 1);
+        assert!(parse(Entity::Vn, &groups).is_ok());
+        for query in [
+            "(".repeat(20_000) + "olang:ja",
+            "-".repeat(20_000) + "olang:ja",
+            "release:(vn:(".repeat(20_000),
+        ] {
+            match parse(Entity::Vn, &query) {
+                Err(FilterError::Syntax { message, .. }) => assert!(message.contains("nested")),
+                other => panic!("expected a nesting error, got {:?}", other),
+            }
+        }
+    }
+}
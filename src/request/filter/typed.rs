@@ -0,0 +1,594 @@
+use serde::{Serialize, Serializer};
+use serde_json::{json, Value};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{BitAnd, BitOr};
+use std::str::FromStr;
+
+use crate::format::character::{CharacterGender, CharacterRole};
+use crate::format::producer::ProducerType;
+use crate::format::release::{ReleaseType, VoicedType};
+use crate::format::schema::{Language, Medium, Platform, StaffRole};
+use crate::format::staff::StaffGender;
+use crate::format::tag::TagFlag;
+use crate::format::vn::{VnDevStatus, VnLength};
+use crate::request::filter::{Filter, Operator};
+
+/// Filter accepted by the endpoint of the query marker T, e.g. TypedFilter<VnQuery>
+///
+/// Created from the field handles of VnFilter, ReleaseFilter, etc. so that a filter
+/// built for one endpoint cannot be sent to another
+pub struct TypedFilter<T> {
+    filter: Filter,
+    _entity: PhantomData<fn() -> T>,
+}
+
+impl<T> TypedFilter<T> {
+    /// Scopes a filter to T without checking its fields,
+    /// for filters VNDB added after this release
+    pub fn unchecked(filter: Filter) -> Self {
+        TypedFilter {
+            filter,
+            _entity: PhantomData,
+        }
+    }
+
+    pub fn and(filters: impl IntoIterator<Item = TypedFilter<T>>) -> Self {
+        Self::unchecked(Filter::and(filters.into_iter().map(|f| f.filter)))
+    }
+
+    pub fn or(filters: impl IntoIterator<Item = TypedFilter<T>>) -> Self {
+        Self::unchecked(Filter::or(filters.into_iter().map(|f| f.filter)))
+    }
+
+    pub fn filter(&self) -> &Filter {
+        &self.filter
+    }
+
+    pub fn into_filter(self) -> Filter {
+        self.filter
+    }
+
+    /// JSON representation sent to VNDB
+    pub fn to_value(&self) -> Value {
+        self.filter.to_value()
+    }
+}
+
+impl<T> Clone for TypedFilter<T> {
+    fn clone(&self) -> Self {
+        Self::unchecked(self.filter.clone())
+    }
+}
+
+impl<T> PartialEq for TypedFilter<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.filter == other.filter
+    }
+}
+
+impl<T> fmt::Debug for TypedFilter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.filter.fmt(f)
+    }
+}
+
+impl<T> BitAnd for TypedFilter<T> {
+    type Output = TypedFilter<T>;
+
+    fn bitand(self, rhs: TypedFilter<T>) -> TypedFilter<T> {
+        Self::unchecked(self.filter & rhs.filter)
+    }
+}
+
+impl<T> BitOr for TypedFilter<T> {
+    type Output = TypedFilter<T>;
+
+    fn bitor(self, rhs: TypedFilter<T>) -> TypedFilter<T> {
+        Self::unchecked(self.filter | rhs.filter)
+    }
+}
+
+impl<T> From<TypedFilter<T>> for Filter {
+    fn from(filter: TypedFilter<T>) -> Self {
+        filter.filter
+    }
+}
+
+impl<T> Serialize for TypedFilter<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.filter.serialize(serializer)
+    }
+}
+
+/// Values a filter field can be compared against
+pub trait FilterValue {
+    fn into_value(self) -> Value;
+}
+
+impl FilterValue for String {
+    fn into_value(self) -> Value {
+        Value::String(self)
+    }
+}
+
+impl FilterValue for u32 {
+    fn into_value(self) -> Value {
+        Value::from(self)
+    }
+}
+
+impl FilterValue for f32 {
+    fn into_value(self) -> Value {
+        Value::from(self)
+    }
+}
+
+/// Filters of another endpoint embedded in a nested field, e.g. a release filter inside a vn filter
+impl<S> FilterValue for TypedFilter<S> {
+    fn into_value(self) -> Value {
+        self.to_value()
+    }
+}
+
+/// Serializes enums from the format module the way they appear in responses
+macro_rules! impl_filter_value_serialize {
+    ($($t:ty),*) => {
+        $(impl FilterValue for $t {
+            fn into_value(self) -> Value {
+                serde_json::to_value(self).unwrap_or(Value::Null)
+            }
+        })*
+    };
+}
+
+impl_filter_value_serialize!(
+    Language,
+    Platform,
+    Medium,
+    StaffRole,
+    StaffGender,
+    TagFlag,
+    CharacterRole,
+    CharacterGender,
+    ProducerType,
+    ReleaseType,
+    VoicedType,
+    VnDevStatus,
+    VnLength
+);
+
+/// Compares a field with = and != only
+pub struct EqField<T, V> {
+    name: &'static str,
+    _marker: PhantomData<fn() -> (T, V)>,
+}
+
+/// Compares a field with = != > >= < and <=
+pub struct OrdField<T, V> {
+    name: &'static str,
+    _marker: PhantomData<fn() -> (T, V)>,
+}
+
+/// Field of endpoint T matching related entries of endpoint S,
+/// e.g. the release field of a vn filter takes a TypedFilter<ReleaseQuery>
+pub type NestedField<T, S> = EqField<T, TypedFilter<S>>;
+
+/// Field that only matches whether a property holds
+pub struct FlagField<T> {
+    name: &'static str,
+    _marker: PhantomData<fn() -> T>,
+}
+
+fn predicate<T>(name: &str, op: Operator, value: Value) -> TypedFilter<T> {
+    TypedFilter::unchecked(Filter::new(name, op, value))
+}
+
+impl<T, V: FilterValue> EqField<T, V> {
+    pub(crate) const fn new(name: &'static str) -> Self {
+        EqField {
+            name,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn eq(self, value: impl Into<V>) -> TypedFilter<T> {
+        predicate(self.name, Operator::Eq, value.into().into_value())
+    }
+
+    pub fn ne(self, value: impl Into<V>) -> TypedFilter<T> {
+        predicate(self.name, Operator::NotEq, value.into().into_value())
+    }
+}
+
+impl<T, V: FilterValue> OrdField<T, V> {
+    pub(crate) const fn new(name: &'static str) -> Self {
+        OrdField {
+            name,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn eq(self, value: impl Into<V>) -> TypedFilter<T> {
+        predicate(self.name, Operator::Eq, value.into().into_value())
+    }
+
+    pub fn ne(self, value: impl Into<V>) -> TypedFilter<T> {
+        predicate(self.name, Operator::NotEq, value.into().into_value())
+    }
+
+    pub fn gt(self, value: impl Into<V>) -> TypedFilter<T> {
+        predicate(self.name, Operator::Gt, value.into().into_value())
+    }
+
+    pub fn ge(self, value: impl Into<V>) -> TypedFilter<T> {
+        predicate(self.name, Operator::GtEq, value.into().into_value())
+    }
+
+    pub fn lt(self, value: impl Into<V>) -> TypedFilter<T> {
+        predicate(self.name, Operator::Lt, value.into().into_value())
+    }
+
+    pub fn le(self, value: impl Into<V>) -> TypedFilter<T> {
+        predicate(self.name, Operator::LtEq, value.into().into_value())
+    }
+}
+
+impl<T> FlagField<T> {
+    pub(crate) const fn new(name: &'static str) -> Self {
+        FlagField {
+            name,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Entries for which the property holds
+    pub fn set(self) -> TypedFilter<T> {
+        predicate(self.name, Operator::Eq, json!(1))
+    }
+
+    /// Entries for which the property does not hold
+    pub fn unset(self) -> TypedFilter<T> {
+        predicate(self.name, Operator::NotEq, json!(1))
+    }
+}
+
+/// Release date as accepted by the released filters
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReleaseDate {
+    /// Full date
+    Date { year: u16, month: u8, day: u8 },
+    /// Any day of the month
+    Month { year: u16, month: u8 },
+    /// Any day of the year
+    Year(u16),
+    /// Date to be announced
+    Tba,
+    /// Unknown date
+    Unknown,
+    /// The current date
+    Today,
+}
+
+impl ReleaseDate {
+    pub fn ymd(year: u16, month: u8, day: u8) -> Self {
+        ReleaseDate::Date { year, month, day }
+    }
+}
+
+impl fmt::Display for ReleaseDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReleaseDate::Date { year, month, day } => {
+                write!(f, "{:04}-{:02}-{:02}", year, month, day)
+            }
+            ReleaseDate::Month { year, month } => write!(f, "{:04}-{:02}", year, month),
+            ReleaseDate::Year(year) => write!(f, "{:04}", year),
+            ReleaseDate::Tba => write!(f, "tba"),
+            ReleaseDate::Unknown => write!(f, "unknown"),
+            ReleaseDate::Today => write!(f, "today"),
+        }
+    }
+}
+
+impl FromStr for ReleaseDate {
+    type Err = String;
+
+    /// Reads "2020-01-01", "2020-01", "2020", "tba", "unknown" or "today"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid release date {:?}", s);
+        match s {
+            "tba" => return Ok(ReleaseDate::Tba),
+            "unknown" => return Ok(ReleaseDate::Unknown),
+            "today" => return Ok(ReleaseDate::Today),
+            _ => {}
+        }
+        let parts = s.split('-').collect::<Vec<_>>();
+        let year = parts[0].parse::<u16>().map_err(|_| invalid())?;
+        let month = match parts.get(1) {
+            Some(month) => Some(
+                month
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|m| (1..=12).contains(m))
+                    .ok_or_else(invalid)?,
+            ),
+            None => None,
+        };
+        let day = match parts.get(2) {
+            Some(day) => Some(
+                day.parse::<u8>()
+                    .ok()
+                    .filter(|d| (1..=31).contains(d))
+                    .ok_or_else(invalid)?,
+            ),
+            None => None,
+        };
+        match (month, day, parts.len()) {
+            (None, None, 1) => Ok(ReleaseDate::Year(year)),
+            (Some(month), None, 2) => Ok(ReleaseDate::Month { year, month }),
+            (Some(month), Some(day), 3) => Ok(ReleaseDate::Date { year, month, day }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl FilterValue for ReleaseDate {
+    fn into_value(self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+/// Ids of database entries, one type per prefix so that an id of one kind of entry
+/// cannot be compared with the id field of another
+macro_rules! entry_ids {
+    ($($(#[$doc:meta])* $name:ident = $prefix:literal;)*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+            pub struct $name(pub u32);
+
+            impl $name {
+                pub const PREFIX: char = $prefix;
+            }
+
+            impl fmt::Display for $name {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    write!(f, "{}{}", Self::PREFIX, self.0)
+                }
+            }
+
+            impl FromStr for $name {
+                type Err = String;
+
+                /// Reads the id with its prefix or only its number
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    let digits = s.strip_prefix(Self::PREFIX).unwrap_or(s);
+                    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                        return Err(format!(
+                            "expected an id such as {}17, found {:?}",
+                            Self::PREFIX,
+                            s
+                        ));
+                    }
+                    digits
+                        .parse()
+                        .map($name)
+                        .map_err(|_| format!("id out of range: {:?}", s))
+                }
+            }
+
+            impl From<u32> for $name {
+                fn from(number: u32) -> Self {
+                    $name(number)
+                }
+            }
+
+            impl FilterValue for $name {
+                fn into_value(self) -> Value {
+                    Value::String(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+entry_ids! {
+    /// Visual novel id, e.g. v17
+    VnId = 'v';
+    /// Release id, e.g. r1
+    ReleaseId = 'r';
+    /// Producer id, e.g. p1
+    ProducerId = 'p';
+    /// Character id, e.g. c1
+    CharacterId = 'c';
+    /// Staff id, e.g. s1
+    StaffId = 's';
+    /// Tag id, e.g. g1
+    TagId = 'g';
+    /// Trait id, e.g. i1
+    TraitId = 'i';
+    /// User id, e.g. u1
+    UserId = 'u';
+}
+
+/// Tag matched by the tag and dtag filters, optionally limited by spoiler and tag level
+#[derive(Clone, PartialEq, Debug)]
+pub struct TagMatch {
+    pub id: TagId,
+    /// Highest spoiler level (0, 1 or 2) a vote may have to count, 2 when only the level is set
+    pub max_spoiler: Option<u8>,
+    /// Lowest tag level (0 to 3) the entry must have
+    pub min_level: Option<f32>,
+}
+
+impl TagMatch {
+    pub fn new(id: impl Into<TagId>) -> Self {
+        TagMatch {
+            id: id.into(),
+            max_spoiler: None,
+            min_level: None,
+        }
+    }
+
+    pub fn max_spoiler(mut self, spoiler: u8) -> Self {
+        self.max_spoiler = Some(spoiler);
+        self
+    }
+
+    pub fn min_level(mut self, level: f32) -> Self {
+        self.min_level = Some(level);
+        self
+    }
+}
+
+impl From<TagId> for TagMatch {
+    fn from(id: TagId) -> Self {
+        TagMatch::new(id)
+    }
+}
+
+impl From<u32> for TagMatch {
+    fn from(id: u32) -> Self {
+        TagMatch::new(id)
+    }
+}
+
+impl FilterValue for TagMatch {
+    fn into_value(self) -> Value {
+        match (self.max_spoiler, self.min_level) {
+            (None, None) => self.id.into_value(),
+            (spoiler, level) => json!([
+                self.id.to_string(),
+                spoiler.unwrap_or(2),
+                level.unwrap_or(0.0)
+            ]),
+        }
+    }
+}
+
+/// Trait matched by the trait and dtrait filters, optionally limited by spoiler level
+#[derive(Clone, PartialEq, Debug)]
+pub struct TraitMatch {
+    pub id: TraitId,
+    /// Highest spoiler level (0, 1 or 2) the trait may have
+    pub max_spoiler: Option<u8>,
+}
+
+impl TraitMatch {
+    pub fn new(id: impl Into<TraitId>) -> Self {
+        TraitMatch {
+            id: id.into(),
+            max_spoiler: None,
+        }
+    }
+
+    pub fn max_spoiler(mut self, spoiler: u8) -> Self {
+        self.max_spoiler = Some(spoiler);
+        self
+    }
+}
+
+impl From<TraitId> for TraitMatch {
+    fn from(id: TraitId) -> Self {
+        TraitMatch::new(id)
+    }
+}
+
+impl From<u32> for TraitMatch {
+    fn from(id: u32) -> Self {
+        TraitMatch::new(id)
+    }
+}
+
+impl FilterValue for TraitMatch {
+    fn into_value(self) -> Value {
+        match self.max_spoiler {
+            None => self.id.into_value(),
+            Some(spoiler) => json!([self.id.to_string(), spoiler]),
+        }
+    }
+}
+
+/// Label of a user's list, matched by the label filter
+#[derive(Clone, PartialEq, Debug)]
+pub struct UserLabel {
+    pub user: UserId,
+    pub label: u32,
+}
+
+impl From<(UserId, u32)> for UserLabel {
+    fn from((user, label): (UserId, u32)) -> Self {
+        UserLabel { user, label }
+    }
+}
+
+impl From<(u32, u32)> for UserLabel {
+    fn from((user, label): (u32, u32)) -> Self {
+        UserLabel {
+            user: UserId(user),
+            label,
+        }
+    }
+}
+
+impl FilterValue for UserLabel {
+    fn into_value(self) -> Value {
+        json!([self.user.to_string(), self.label])
+    }
+}
+
+/// Pair of numbers such as a [width, height] resolution or a [month, day] birthday
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Pair(pub u32, pub u32);
+
+impl From<(u32, u32)> for Pair {
+    fn from((first, second): (u32, u32)) -> Self {
+        Pair(first, second)
+    }
+}
+
+impl FilterValue for Pair {
+    fn into_value(self) -> Value {
+        json!([self.0, self.1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::filter::{CharacterFilter, VnFilter};
+
+    fn value<T>(filter: TypedFilter<T>) -> Value {
+        filter.into_filter().into()
+    }
+
+    #[test]
+    fn writes_typed_match_ids() {
+        assert_eq!(value(VnFilter::tag().eq(7)), json!(["tag", "=", "g7"]));
+        assert_eq!(
+            value(VnFilter::dtag().eq(TagMatch::new(TagId(7)).max_spoiler(1).min_level(1.5))),
+            json!(["dtag", "=", ["g7", 1, 1.5]])
+        );
+        assert_eq!(
+            value(CharacterFilter::trait_().eq(TraitMatch::new(5).max_spoiler(0))),
+            json!(["trait", "=", ["i5", 0]])
+        );
+        assert_eq!(
+            value(VnFilter::label().eq((UserId(2), 7))),
+            json!(["label", "=", ["u2", 7]])
+        );
+    }
+}
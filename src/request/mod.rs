@@ -1,2 +1,7 @@
+pub mod browse;
+pub mod endpoint;
+pub mod fields;
+pub mod filter;
+pub mod projection;
 pub mod query;
 pub mod response;
@@ -0,0 +1,138 @@
+use serde::de::DeserializeOwned;
+
+use crate::request::endpoint::Endpoint;
+
+pub use vndb_api_derive::VndbProjection;
+
+/// Structs choosing their own fields, usually derived with #[derive(VndbProjection)]
+///
+/// ```ignore
+/// #[derive(Deserialize, VndbProjection)]
+/// #[vndb(entity = "vn")]
+/// struct Novel {
+///     id: String,
+///     title: String,
+///     rating: Option<f32>,
+///     #[vndb(nested)]
+///     developers: Vec<Developer>,
+/// }
+///
+/// #[derive(Deserialize, VndbProjection)]
+/// struct Developer {
+///     name: String,
+/// }
+/// ```
+/// selects "id,title,rating,developers.name"
+pub trait VndbProjection: DeserializeOwned {
+    /// Dotted paths of the fields the struct reads, e.g. ["title", "developers.name"]
+    fn field_paths() -> Vec<String>;
+
+    /// Comma separated fields as sent in a query
+    fn fields() -> String {
+        Self::field_paths().join(",")
+    }
+}
+
+/// Projections of an entry type that can be searched for directly
+pub trait EntityProjection: VndbProjection {
+    /// Query marker of the endpoint, e.g. VnQuery
+    type Query: Endpoint;
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::request::query::{ReleaseQuery, VnQuery};
+
+    #[derive(Deserialize, VndbProjection)]
+    #[vndb(entity = "vn")]
+    #[allow(dead_code)]
+    struct Novel {
+        id: String,
+        #[serde(rename = "title")]
+        name: String,
+        rating: Option<f32>,
+        #[vndb(nested)]
+        developers: Vec<Developer>,
+        #[vndb(nested)]
+        image: Option<Image>,
+        #[vndb(skip)]
+        #[serde(skip)]
+        local: u32,
+    }
+
+    #[derive(Deserialize, VndbProjection)]
+    #[allow(dead_code)]
+    struct Developer {
+        name: String,
+        r#type: String,
+    }
+
+    #[derive(Deserialize, VndbProjection)]
+    #[allow(dead_code)]
+    struct Image {
+        url: String,
+        #[vndb(rename = "sexual")]
+        explicit: f32,
+        #[vndb(nested)]
+        thumbnail: Option<Box<Thumbnail>>,
+    }
+
+    #[derive(Deserialize, VndbProjection)]
+    #[allow(dead_code)]
+    struct Thumbnail {
+        dims: Vec<u32>,
+    }
+
+    #[derive(Deserialize, VndbProjection)]
+    #[vndb(entity = "release")]
+    #[serde(rename_all = "UPPERCASE")]
+    #[allow(dead_code)]
+    struct Shouting {
+        min_age: Option<u8>,
+        #[serde(rename = "title")]
+        name: String,
+    }
+
+    #[derive(Deserialize, VndbProjection)]
+    #[vndb(rename_all = "kebab-case")]
+    #[serde(rename_all = "camelCase")]
+    #[allow(dead_code)]
+    struct Overridden {
+        has_ero: bool,
+    }
+
+    fn entity_of<P: EntityProjection>() -> &'static str {
+        std::any::type_name::<P::Query>()
+    }
+
+    #[test]
+    fn lists_nested_renamed_and_wrapped_fields() {
+        assert_eq!(
+            Novel::field_paths(),
+            [
+                "id",
+                "title",
+                "rating",
+                "developers.name",
+                "developers.type",
+                "image.url",
+                "image.sexual",
+                "image.thumbnail.dims",
+            ]
+        );
+        assert_eq!(entity_of::<Novel>(), std::any::type_name::<VnQuery>());
+    }
+
+    #[test]
+    fn renames_all_fields() {
+        assert_eq!(Shouting::fields(), "MIN_AGE,title");
+        assert_eq!(
+            entity_of::<Shouting>(),
+            std::any::type_name::<ReleaseQuery>()
+        );
+        assert_eq!(Overridden::fields(), "has-ero");
+    }
+}
@@ -1,43 +1,50 @@
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
+
+use crate::format::error::{FilterError, QueryError, QueryViolation};
+use crate::request::filter::{Filter, FilterEntity, Operator, TypedFilter};
+use crate::request::response::Response;
+use serde_json::Value;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
-use serde_json::Value;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct VnQuery {}
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ReleaseQuery {}
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ProducerQuery {}
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct CharacterQuery {}
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct StaffQuery {}
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct TagQuery {}
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct TraitQuery {}
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct UListQuery {}
 
-#[derive(Deserialize, Serialize, Debug)]
+/// Largest number of results per page VNDB returns
+pub const MAX_RESULTS: u32 = 100;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Query<T> {
     /// Used to determine which database items to fetch
-    filters: Option<Value>,
+    pub(crate) filters: Option<Value>,
     /// List of fields to fetch for each database item
     fields: Option<String>,
     /// Field to sort on
-    sort: Option<SortField>,
+    pub(crate) sort: Option<SortField>,
     /// Set to true to sort in descending order
-    reverse: Option<bool>,
+    pub(crate) reverse: Option<bool>,
     /// Number of results per page max 100
     /// can also be set to 0 if you’re not interested in the results at all
-    results: Option<u8>,
+    results: Option<u32>,
     /// Page number to request starting from 1
-    page: Option<u8>,
+    pub(crate) page: Option<u32>,
     /// User ID
-    user: Option<String>,
+    pub(crate) user: Option<String>,
     /// Whether the response should include the count field
     count: Option<bool>,
     /// Whether the response should include the compact_filters field
@@ -48,14 +55,34 @@ pub struct Query<T> {
     _phantom: Option<PhantomData<T>>,
 }
 
+impl<T> Query<T> {
+    /// Page this query requests
+    pub fn page(&self) -> u32 {
+        self.page.unwrap_or(1)
+    }
+
+    /// The same query for the following page, None when the response reports no more results
+    pub fn next_page<R>(&self, response: &Response<R>) -> Option<Query<T>>
+    where
+        T: Clone,
+    {
+        if !response.more {
+            return None;
+        }
+        let mut next = self.clone();
+        next.page = Some(self.page().checked_add(1)?);
+        Some(next)
+    }
+}
+
 #[derive(Debug)]
 pub struct QueryBuilder<T> {
     pub filters: Option<Value>,
     pub fields: Option<String>,
     pub sort: Option<SortField>,
     pub reverse: Option<bool>,
-    pub results: Option<u8>,
-    pub page: Option<u8>,
+    pub results: Option<u32>,
+    pub page: Option<u32>,
     pub user: Option<String>,
     pub count: Option<bool>,
     pub compact_filters: Option<bool>,
@@ -80,16 +107,44 @@ impl<T> QueryBuilder<T> {
         }
     }
 
-    pub fn filters(mut self, filters: &String) -> Self {
-        match serde_json::from_str(filters) {
-            Ok(filters) => {
-                self.filters = filters;
-                return self;
-            }
-            Err(error) => { 
-                panic!("Invalid json error: {}", error); 
-            }
-        }
+    /// Filters built from the fields of the matching endpoint, e.g. VnFilter for QueryBuilder<VnQuery>
+    pub fn filters(mut self, filters: TypedFilter<T>) -> Self {
+        self.filters = Some(filters.to_value());
+        self
+    }
+
+    /// Filters written as JSON, fails instead of sending filters VNDB cannot read
+    pub fn try_filters(mut self, filters: &str) -> Result<Self, FilterError> {
+        let value: Value = serde_json::from_str(filters)?;
+        self.filters = Some(Filter::from_value(&value)?.to_value());
+        Ok(self)
+    }
+
+    /// Filters written as a compact string, e.g. the compact_filters of a response
+    pub fn try_compact_filters(mut self, compact: &str) -> Result<Self, FilterError>
+    where
+        T: FilterEntity,
+    {
+        self.filters = Some(TypedFilter::<T>::from_compact(compact)?.to_value());
+        Ok(self)
+    }
+
+    /// Filters written in the text query language, e.g. "olang:ja released>=2020-01-01 -tag:g134"
+    pub fn try_text_filters(mut self, query: &str) -> Result<Self, FilterError>
+    where
+        T: FilterEntity,
+    {
+        self.filters = Some(query.parse::<TypedFilter<T>>()?.to_value());
+        Ok(self)
+    }
+
+    /// Field to sort on, Searchrank also needs a search filter
+    pub fn sort(mut self, sort: T::Sort) -> Self
+    where
+        T: Sortable,
+    {
+        self.sort = Some(sort.into());
+        self
     }
 
     pub fn reverse(mut self) -> Self {
@@ -97,18 +152,20 @@ impl<T> QueryBuilder<T> {
         self
     }
 
-    pub fn results(mut self, num_results: u8) -> Self {
-        self.results = Some(num_results.clamp(0, 100));
+    /// Number of results per page, build() fails when it is above 100
+    pub fn results(mut self, num_results: u32) -> Self {
+        self.results = Some(num_results);
         self
     }
 
-    pub fn page(mut self, page_num: u8) -> Self {
+    /// Page number starting from 1, build() fails when it is 0
+    pub fn page(mut self, page_num: u32) -> Self {
         self.page = Some(page_num);
         self
     }
 
-    pub fn user(mut self, user_id: &String) -> Self {
-        self.user = Some(user_id.clone());
+    pub fn user(mut self, user_id: &str) -> Self {
+        self.user = Some(user_id.to_string());
         self
     }
 
@@ -122,8 +179,32 @@ impl<T> QueryBuilder<T> {
         self
     }
 
-    pub fn build(self) -> Query<T> {
-        Query::<T> {
+    /// Fails with every value VNDB would reject
+    pub fn build(self) -> Result<Query<T>, QueryError>
+    where
+        T: Sortable,
+    {
+        let mut violations = Vec::new();
+        if let Some(results) = self.results.filter(|results| *results > MAX_RESULTS) {
+            violations.push(QueryViolation::TooManyResults(results));
+        }
+        if self.page == Some(0) {
+            violations.push(QueryViolation::PageZero);
+        }
+        match self.sort {
+            Some(sort) if !T::SORTS.contains(&sort) => {
+                violations.push(QueryViolation::UnsupportedSort(sort));
+            }
+            Some(SortField::Searchrank) if !has_search(self.filters.as_ref()) => {
+                violations.push(QueryViolation::SearchrankWithoutSearch);
+            }
+            _ => {}
+        }
+        if !violations.is_empty() {
+            return Err(QueryError { violations });
+        }
+
+        Ok(Query::<T> {
             filters: self.filters,
             fields: self.fields,
             sort: self.sort,
@@ -135,11 +216,31 @@ impl<T> QueryBuilder<T> {
             compact_filters: self.compact_filters,
             normalized_filters: self.normalized_filters,
             _phantom: None,
+        })
+    }
+}
+
+// searches inside and/or groups count, a search inside the filter of a nested field such as release
+// does not rank results and is not looked at
+fn has_search(filters: Option<&Value>) -> bool {
+    fn search(filter: &Filter) -> bool {
+        match filter {
+            Filter::And(filters) | Filter::Or(filters) => filters.iter().any(search),
+            Filter::Predicate { field, op, .. } => field == "search" && *op == Operator::Eq,
         }
     }
+    filters
+        .and_then(|filters| Filter::from_value(filters).ok())
+        .is_some_and(|filter| search(&filter))
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+impl<T> Default for QueryBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum SortField {
     Id,
@@ -159,6 +260,81 @@ pub enum SortField {
     Finished,
 }
 
+/// Query markers with the sort orders their endpoint accepts
+pub trait Sortable {
+    /// Sort orders of the endpoint, e.g. VnSort for VnQuery
+    type Sort: Into<SortField> + Copy;
+    /// Every SortField the endpoint accepts
+    const SORTS: &'static [SortField];
+}
+
+/// Declares the sort orders of an endpoint as an enum convertible into SortField
+macro_rules! sort_orders {
+    ($(#[$doc:meta])* $name:ident for $marker:ty { $($variant:ident),* $(,)? }) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub enum $name {
+            $($variant),*
+        }
+
+        impl From<$name> for SortField {
+            fn from(sort: $name) -> Self {
+                match sort {
+                    $($name::$variant => SortField::$variant),*
+                }
+            }
+        }
+
+        impl Sortable for $marker {
+            type Sort = $name;
+            const SORTS: &'static [SortField] = &[$(SortField::$variant),*];
+        }
+    };
+}
+
+sort_orders! {
+    /// Sort orders accepted by /vn
+    VnSort for VnQuery { Id, Title, Released, Rating, Votecount, Searchrank }
+}
+
+sort_orders! {
+    /// Sort orders accepted by /release
+    ReleaseSort for ReleaseQuery { Id, Title, Released, Searchrank }
+}
+
+sort_orders! {
+    /// Sort orders accepted by /producer
+    ProducerSort for ProducerQuery { Id, Name, Searchrank }
+}
+
+sort_orders! {
+    /// Sort orders accepted by /character
+    CharacterSort for CharacterQuery { Id, Name, Searchrank }
+}
+
+sort_orders! {
+    /// Sort orders accepted by /staff
+    StaffSort for StaffQuery { Id, Name, Searchrank }
+}
+
+sort_orders! {
+    /// Sort orders accepted by /tag
+    TagSort for TagQuery { Id, Name, VnCount, Searchrank }
+}
+
+sort_orders! {
+    /// Sort orders accepted by /trait
+    TraitSort for TraitQuery { Id, Name, CharCount, Searchrank }
+}
+
+sort_orders! {
+    /// Sort orders accepted by /ulist
+    UListSort for UListQuery {
+        Id, Title, Released, Rating, Votecount, Voted, Vote, Added, Lastmod, Started, Finished,
+        Searchrank,
+    }
+}
+
 pub struct VnFieldChoices(pub Vec<VnField>);
 
 #[derive(Serialize, Debug, EnumIter)]
@@ -375,6 +551,12 @@ impl VnFieldChoices {
     }
 }
 
+impl Default for VnFieldChoices {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl QueryBuilder<VnQuery> {
     pub fn fields(mut self, fields: VnFieldChoices) -> Self {
         self.fields = Some(
@@ -392,19 +574,6 @@ impl QueryBuilder<VnQuery> {
         self.count = Some(true);
         self
     }
-
-    pub fn sort(mut self, sort_field: SortField) -> Self {
-        match sort_field {
-            SortField::Id
-            | SortField::Title
-            | SortField::Released
-            | SortField::Rating
-            | SortField::Votecount
-            | SortField::Searchrank => self.sort = Some(sort_field),
-            _ => return self,
-        }
-        self
-    }
 }
 
 pub struct ReleaseFieldChoices(pub Vec<ReleaseField>);
@@ -511,6 +680,12 @@ impl ReleaseFieldChoices {
     }
 }
 
+impl Default for ReleaseFieldChoices {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl QueryBuilder<ReleaseQuery> {
     pub fn fields(mut self, fields: ReleaseFieldChoices) -> Self {
         self.fields = Some(
@@ -528,16 +703,6 @@ impl QueryBuilder<ReleaseQuery> {
         self.count = Some(true);
         self
     }
-
-    pub fn sort(mut self, sort_field: SortField) -> Self {
-        match sort_field {
-            SortField::Id | SortField::Title | SortField::Released | SortField::Searchrank => {
-                self.sort = Some(sort_field)
-            }
-            _ => return self,
-        }
-        self
-    }
 }
 
 pub struct ProducerFieldChoices(pub Vec<ProducerField>);
@@ -572,6 +737,12 @@ impl ProducerFieldChoices {
     }
 }
 
+impl Default for ProducerFieldChoices {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl QueryBuilder<ProducerQuery> {
     pub fn fields(mut self, fields: ProducerFieldChoices) -> Self {
         self.fields = Some(
@@ -589,14 +760,6 @@ impl QueryBuilder<ProducerQuery> {
         self.count = Some(true);
         self
     }
-
-    pub fn sort(mut self, sort_field: SortField) -> Self {
-        match sort_field {
-            SortField::Id | SortField::Name | SortField::Searchrank => self.sort = Some(sort_field),
-            _ => return self,
-        }
-        self
-    }
 }
 
 pub struct CharacterFieldChoices(pub Vec<CharacterField>);
@@ -699,6 +862,12 @@ impl CharacterFieldChoices {
     }
 }
 
+impl Default for CharacterFieldChoices {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl QueryBuilder<CharacterQuery> {
     pub fn fields(mut self, fields: CharacterFieldChoices) -> Self {
         self.fields = Some(
@@ -716,14 +885,6 @@ impl QueryBuilder<CharacterQuery> {
         self.count = Some(true);
         self
     }
-
-    pub fn sort(mut self, sort_field: SortField) -> Self {
-        match sort_field {
-            SortField::Id | SortField::Name | SortField::Searchrank => self.sort = Some(sort_field),
-            _ => return self,
-        }
-        self
-    }
 }
 
 pub struct StaffFieldChoices(pub Vec<StaffField>);
@@ -780,6 +941,12 @@ impl StaffFieldChoices {
     }
 }
 
+impl Default for StaffFieldChoices {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl QueryBuilder<StaffQuery> {
     pub fn fields(mut self, fields: StaffFieldChoices) -> Self {
         self.fields = Some(
@@ -797,14 +964,6 @@ impl QueryBuilder<StaffQuery> {
         self.count = Some(true);
         self
     }
-
-    pub fn sort(mut self, sort_field: SortField) -> Self {
-        match sort_field {
-            SortField::Id | SortField::Name | SortField::Searchrank => self.sort = Some(sort_field),
-            _ => return self,
-        }
-        self
-    }
 }
 
 pub struct TagFieldChoices(pub Vec<TagField>);
@@ -839,6 +998,12 @@ impl TagFieldChoices {
     }
 }
 
+impl Default for TagFieldChoices {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl QueryBuilder<TagQuery> {
     pub fn fields(mut self, fields: TagFieldChoices) -> Self {
         self.fields = Some(
@@ -856,16 +1021,6 @@ impl QueryBuilder<TagQuery> {
         self.count = Some(true);
         self
     }
-
-    pub fn sort(mut self, sort_field: SortField) -> Self {
-        match sort_field {
-            SortField::Id | SortField::Name | SortField::VnCount | SortField::Searchrank => {
-                self.sort = Some(sort_field)
-            }
-            _ => return self,
-        }
-        self
-    }
 }
 
 pub struct TraitFieldChoices(pub Vec<TraitField>);
@@ -902,6 +1057,12 @@ impl TraitFieldChoices {
     }
 }
 
+impl Default for TraitFieldChoices {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl QueryBuilder<TraitQuery> {
     pub fn fields(mut self, fields: TraitFieldChoices) -> Self {
         self.fields = Some(
@@ -919,16 +1080,6 @@ impl QueryBuilder<TraitQuery> {
         self.count = Some(true);
         self
     }
-
-    pub fn sort(mut self, sort_field: SortField) -> Self {
-        match sort_field {
-            SortField::Id | SortField::Name | SortField::CharCount | SortField::Searchrank => {
-                self.sort = Some(sort_field)
-            }
-            _ => return self,
-        }
-        self
-    }
 }
 
 pub struct UListFieldChoices(pub Vec<UListField>);
@@ -1251,6 +1402,12 @@ impl UListFieldChoices {
     }
 }
 
+impl Default for UListFieldChoices {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl QueryBuilder<UListQuery> {
     pub fn fields(mut self, fields: UListFieldChoices) -> Self {
         self.fields = Some(
@@ -1263,23 +1420,75 @@ impl QueryBuilder<UListQuery> {
         );
         self
     }
+}
 
-    pub fn sort(mut self, sort_field: SortField) -> Self {
-        match sort_field {
-            SortField::Id
-            | SortField::Title
-            | SortField::Released
-            | SortField::Rating
-            | SortField::Votecount
-            | SortField::Voted
-            | SortField::Vote
-            | SortField::Added
-            | SortField::Lastmod
-            | SortField::Started
-            | SortField::Finished
-            | SortField::Searchrank => self.sort = Some(sort_field),
-            _ => return self,
-        }
-        self
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::schema::Language;
+    use crate::request::filter::{ReleaseFilter, VnFilter};
+
+    fn violations<T: Sortable>(builder: QueryBuilder<T>) -> Vec<QueryViolation> {
+        builder
+            .build()
+            .map(|_| Vec::new())
+            .unwrap_or_else(|err| err.violations)
+    }
+
+    #[test]
+    fn accepts_page_sizes_up_to_the_maximum() {
+        assert!(QueryBuilder::<VnQuery>::new().results(100).build().is_ok());
+        assert!(QueryBuilder::<VnQuery>::new().results(0).build().is_ok());
+    }
+
+    #[test]
+    fn accepts_searchrank_with_a_search() {
+        let search = QueryBuilder::<VnQuery>::new()
+            .filters(VnFilter::olang().eq(Language::Japanese) | VnFilter::search().eq("fate"))
+            .sort(VnSort::Searchrank);
+        assert!(search.build().is_ok());
+    }
+
+    #[test]
+    fn rejects_too_many_results_and_page_zero() {
+        assert_eq!(
+            violations(QueryBuilder::<VnQuery>::new().results(101).page(0)),
+            [
+                QueryViolation::TooManyResults(101),
+                QueryViolation::PageZero
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_sorts_of_other_endpoints() {
+        let mut builder = QueryBuilder::<ReleaseQuery>::new();
+        builder.sort = Some(SortField::Rating);
+        assert_eq!(
+            violations(builder),
+            [QueryViolation::UnsupportedSort(SortField::Rating)]
+        );
+    }
+
+    #[test]
+    fn rejects_searchrank_without_search() {
+        assert_eq!(
+            violations(QueryBuilder::<VnQuery>::new().sort(VnSort::Searchrank)),
+            [QueryViolation::SearchrankWithoutSearch]
+        );
+        let negated = QueryBuilder::<VnQuery>::new()
+            .filters(VnFilter::search().ne("fate"))
+            .sort(VnSort::Searchrank);
+        assert_eq!(
+            violations(negated),
+            [QueryViolation::SearchrankWithoutSearch]
+        );
+        let nested = QueryBuilder::<VnQuery>::new()
+            .filters(VnFilter::release().eq(ReleaseFilter::search().eq("fate")))
+            .sort(VnSort::Searchrank);
+        assert_eq!(
+            violations(nested),
+            [QueryViolation::SearchrankWithoutSearch]
+        );
     }
 }
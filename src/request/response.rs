@@ -13,3 +13,12 @@ pub struct Response<T> {
     /// Normalized JSON representation of the filters given in the query
     pub normalized_filters: Option<serde_json::Value>,
 }
+
+/// Search response whose results are left as JSON values, see VndbApiClient::search_raw()
+#[derive(Debug)]
+pub struct RawResponse {
+    /// Response read from body
+    pub response: Response<serde_json::Value>,
+    /// Body exactly as sent by the server
+    pub body: Vec<u8>,
+}
@@ -0,0 +1,338 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Fields, GenericArgument, LitStr,
+    PathArguments, Type,
+};
+
+/// Entry types accepted by #[vndb(entity = "...")] with their query marker
+const ENTITIES: &[(&str, &str)] = &[
+    ("vn", "VnQuery"),
+    ("release", "ReleaseQuery"),
+    ("producer", "ProducerQuery"),
+    ("character", "CharacterQuery"),
+    ("staff", "StaffQuery"),
+    ("tag", "TagQuery"),
+    ("trait", "TraitQuery"),
+    ("ulist", "UListQuery"),
+];
+
+/// Implements VndbProjection, listing the kana fields a struct reads from its field names
+///
+/// - `#[vndb(entity = "vn")]` on the struct also implements EntityProjection so the struct can
+///   be searched for directly, nested structs leave it out
+/// - `#[vndb(nested)]` on a field selects the fields of its struct type under the field's name,
+///   the type may be wrapped in Option or Vec
+/// - `#[vndb(rename = "type")]` or `#[serde(rename = "type")]` changes the name of a field
+/// - `#[vndb(rename_all = "camelCase")]` or `#[serde(rename_all = "camelCase")]` on the struct
+///   changes the names of the fields that are not renamed one by one, with serde's rules
+/// - `#[vndb(skip)]` leaves a field out of the list
+#[proc_macro_derive(VndbProjection, attributes(vndb))]
+pub fn derive_vndb_projection(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "VndbProjection needs a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "VndbProjection can only be derived for structs",
+            ))
+        }
+    };
+
+    let options = StructOptions::parse(&input.attrs)?;
+    let mut paths = Vec::new();
+    for field in fields {
+        let field_options = FieldOptions::parse(&field.attrs)?;
+        if field_options.skip {
+            continue;
+        }
+        let ident = field.ident.as_ref().expect("named fields have a name");
+        let field_name = field_options.rename.unwrap_or_else(|| {
+            let ident = ident.to_string();
+            let ident = ident.strip_prefix("r#").unwrap_or(&ident);
+            match &options.rename_all {
+                Some(rule) => rule.apply(ident),
+                None => ident.to_string(),
+            }
+        });
+        if field_options.nested {
+            let inner = inner_type(&field.ty);
+            paths.push(quote! {
+                for path in <#inner as ::vndb_api::request::projection::VndbProjection>::field_paths() {
+                    paths.push(format!("{}.{}", #field_name, path));
+                }
+            });
+        } else {
+            paths.push(quote! {
+                paths.push(#field_name.to_string());
+            });
+        }
+    }
+
+    let entity_impl = match options.entity {
+        Some(entity) => {
+            let (_, marker) = ENTITIES
+                .iter()
+                .find(|(name, _)| *name == entity.value())
+                .ok_or_else(|| {
+                    let names = ENTITIES.iter().map(|e| e.0).collect::<Vec<_>>().join(", ");
+                    syn::Error::new_spanned(
+                        &entity,
+                        format!("unknown entity, expected one of {}", names),
+                    )
+                })?;
+            let marker = syn::Ident::new(marker, entity.span());
+            quote! {
+                impl #impl_generics ::vndb_api::request::projection::EntityProjection for #name #ty_generics #where_clause {
+                    type Query = ::vndb_api::request::query::#marker;
+                }
+            }
+        }
+        None => quote! {},
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::vndb_api::request::projection::VndbProjection for #name #ty_generics #where_clause {
+            fn field_paths() -> ::std::vec::Vec<::std::string::String> {
+                let mut paths = ::std::vec::Vec::new();
+                #(#paths)*
+                paths
+            }
+        }
+
+        #entity_impl
+    })
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    nested: bool,
+    skip: bool,
+    rename: Option<String>,
+}
+
+impl FieldOptions {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = FieldOptions::default();
+        for attr in attrs {
+            if attr.path().is_ident("vndb") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("nested") {
+                        options.nested = true;
+                    } else if meta.path.is_ident("skip") {
+                        options.skip = true;
+                    } else if meta.path.is_ident("rename") {
+                        options.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                    } else {
+                        return Err(meta.error("expected nested, skip or rename"));
+                    }
+                    Ok(())
+                })?;
+            } else if attr.path().is_ident("serde") && options.rename.is_none() {
+                // only the plain rename = "..." form, other serde options are left to serde
+                let _ = attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        if let Ok(value) = meta.value() {
+                            options.rename = Some(value.parse::<LitStr>()?.value());
+                        }
+                    } else if meta.input.peek(syn::Token![=]) {
+                        meta.value()?.parse::<syn::Expr>()?;
+                    } else if meta.input.peek(syn::token::Paren) {
+                        meta.parse_nested_meta(|_| Ok(()))?;
+                    }
+                    Ok(())
+                });
+            }
+        }
+        Ok(options)
+    }
+}
+
+#[derive(Default)]
+struct StructOptions {
+    entity: Option<LitStr>,
+    rename_all: Option<RenameRule>,
+}
+
+impl StructOptions {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = StructOptions::default();
+        let mut serde_rule = None;
+        for attr in attrs {
+            if attr.path().is_ident("vndb") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("entity") {
+                        options.entity = Some(meta.value()?.parse::<LitStr>()?);
+                    } else if meta.path.is_ident("rename_all") {
+                        options.rename_all = Some(RenameRule::parse(meta.value()?.parse()?)?);
+                    } else {
+                        return Err(meta.error("expected entity or rename_all"));
+                    }
+                    Ok(())
+                })?;
+            } else if attr.path().is_ident("serde") {
+                // rename_all = "..." or rename_all(deserialize = "..."), the names kana sends
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename_all") {
+                        if meta.input.peek(syn::token::Paren) {
+                            meta.parse_nested_meta(|inner| {
+                                let rule = inner.value()?.parse::<LitStr>()?;
+                                if inner.path.is_ident("deserialize") {
+                                    serde_rule = Some(RenameRule::parse(rule)?);
+                                }
+                                Ok(())
+                            })?;
+                        } else {
+                            serde_rule = Some(RenameRule::parse(meta.value()?.parse()?)?);
+                        }
+                    } else if meta.input.peek(syn::Token![=]) {
+                        meta.value()?.parse::<syn::Expr>()?;
+                    } else if meta.input.peek(syn::token::Paren) {
+                        meta.parse_nested_meta(|_| Ok(()))?;
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+        options.rename_all = options.rename_all.or(serde_rule);
+        Ok(options)
+    }
+}
+
+/// Case conversions of serde's rename_all, applied to snake_case field names
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    const NAMES: &'static [(&'static str, RenameRule)] = &[
+        ("lowercase", RenameRule::Lower),
+        ("UPPERCASE", RenameRule::Upper),
+        ("PascalCase", RenameRule::Pascal),
+        ("camelCase", RenameRule::Camel),
+        ("snake_case", RenameRule::Snake),
+        ("SCREAMING_SNAKE_CASE", RenameRule::ScreamingSnake),
+        ("kebab-case", RenameRule::Kebab),
+        ("SCREAMING-KEBAB-CASE", RenameRule::ScreamingKebab),
+    ];
+
+    fn parse(name: LitStr) -> syn::Result<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(rule, _)| *rule == name.value())
+            .map(|(_, rule)| *rule)
+            .ok_or_else(|| {
+                let names = Self::NAMES
+                    .iter()
+                    .map(|r| r.0)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                syn::Error::new_spanned(
+                    &name,
+                    format!("unknown rename_all rule, expected one of {}", names),
+                )
+            })
+    }
+
+    fn apply(self, field: &str) -> String {
+        match self {
+            RenameRule::Lower | RenameRule::Snake => field.to_string(),
+            RenameRule::Upper | RenameRule::ScreamingSnake => field.to_ascii_uppercase(),
+            RenameRule::Pascal => field
+                .split('_')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                        None => String::new(),
+                    }
+                })
+                .collect(),
+            RenameRule::Camel => {
+                let pascal = RenameRule::Pascal.apply(field);
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => pascal,
+                }
+            }
+            RenameRule::Kebab => field.replace('_', "-"),
+            RenameRule::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+}
+
+/// Type inside Option, Vec and Box wrappers, e.g. Relation for Option<Vec<Relation>>
+fn inner_type(ty: &Type) -> &Type {
+    if let Type::Path(path) = ty {
+        if let Some(segment) = path.path.segments.last() {
+            let wrapper = matches!(segment.ident.to_string().as_str(), "Option" | "Vec" | "Box");
+            if let (true, PathArguments::AngleBracketed(args)) = (wrapper, &segment.arguments) {
+                if let Some(GenericArgument::Type(inner)) = args.args.first() {
+                    return inner_type(inner);
+                }
+            }
+        }
+    }
+    ty
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_rename_rules() {
+        let renamed = |rule: &str| {
+            let rule = RenameRule::parse(LitStr::new(rule, proc_macro2::Span::call_site()));
+            rule.unwrap().apply("min_age")
+        };
+        assert_eq!(renamed("lowercase"), "min_age");
+        assert_eq!(renamed("UPPERCASE"), "MIN_AGE");
+        assert_eq!(renamed("PascalCase"), "MinAge");
+        assert_eq!(renamed("camelCase"), "minAge");
+        assert_eq!(renamed("snake_case"), "min_age");
+        assert_eq!(renamed("SCREAMING_SNAKE_CASE"), "MIN_AGE");
+        assert_eq!(renamed("kebab-case"), "min-age");
+        assert_eq!(renamed("SCREAMING-KEBAB-CASE"), "MIN-AGE");
+    }
+
+    #[test]
+    fn rejects_unknown_rename_rules() {
+        let input: DeriveInput = syn::parse_quote! {
+            #[serde(rename_all = "Title Case")]
+            struct Vn {
+                id: String,
+            }
+        };
+        let error = expand(input).unwrap_err().to_string();
+        assert!(error.starts_with("unknown rename_all rule"), "{}", error);
+    }
+}